@@ -1,35 +1,239 @@
+use crate::douconel_history::{History, record, record_all};
+use crate::douconel_properties::Properties;
 use bimap::BiHashMap;
 use core::panic;
 use itertools::Itertools;
+use rand::Rng;
 use rand::seq::IteratorRandom;
 use serde::{Deserialize, Serialize};
 use slotmap::Key;
 use slotmap::SecondaryMap;
 use slotmap::SlotMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone, Serialize, Deserialize)]
-pub enum MeshError<VertID> {
-    #[error("({0}, {1}) does not have a twin (mesh is not a closed 2-manifold)")]
-    NoTwin(VertID, VertID),
-    #[error("({0}, {1}) exists multiple times (mesh is not a closed 2-manifold)")]
+pub enum MeshError<VertID, EdgeID, FaceID> {
+    #[error("({0}, {1}) exists multiple times (mesh is not a 2-manifold)")]
     DuplicateEdge(VertID, VertID),
+    #[error("{0} has more than one fan of faces (mesh is not a 2-manifold)")]
+    NonManifoldVertex(VertID),
+    #[error("({0}, {1}) is on the boundary")]
+    BoundaryEdge(VertID, VertID),
+    #[error("({0}, {1}) is not adjacent to two triangles")]
+    NotTriangular(VertID, VertID),
+    #[error("({0}, {1}) is not a diagonal of the face")]
+    NotDiagonal(VertID, VertID),
+    #[error("Removing ({0}, {1}) would create a dangling vertex or a face that is bounded twice by the same edge")]
+    NotMergeable(VertID, VertID),
+    #[error("Collapsing ({0}, {1}) violates the link condition (mesh would not be a 2-manifold)")]
+    LinkCondition(VertID, VertID),
+    #[error("({0}, {1}) are already connected (flipping would create a duplicate edge)")]
+    AlreadyConnected(VertID, VertID),
+    #[error("{0:?} is not a (valid) vertex of the mesh")]
+    InvalidVertex(VertID),
+    #[error("{0:?} is not a (valid) edge of the mesh")]
+    InvalidEdge(EdgeID),
+    #[error("{0:?} is not a (valid) face of the mesh")]
+    InvalidFace(FaceID),
+    #[error("{0:?} is not a boundary edge")]
+    NotBoundaryEdge(EdgeID),
+    #[error("Boundary loops have a different number of edges ({0} and {1})")]
+    BoundaryLengthMismatch(usize, usize),
+    #[error("({0}, {1}) is not an edge of the mesh")]
+    MissingEdge(VertID, VertID),
     #[error("Mesh is not orientable")]
     NotOrientable,
     #[error("Mesh is not connected")]
     NotConnected,
+    #[error("A transaction is already open")]
+    TransactionOpen,
+    #[error("No transaction is open")]
+    NoTransaction,
+    #[error("{0:?} is not a quad (mesh is not a quad mesh)")]
+    NotQuad(FaceID),
+    #[error("Patch {0} is not a disk (its border is not a single loop)")]
+    PatchNotDisk(usize),
+    #[error("Patch {0} has less than three corners")]
+    PatchTooFewCorners(usize),
+    #[error("Patches {0} and {1} have different sides between the same two corners")]
+    PatchSidesShareCorners(usize, usize),
     #[error("Unknown error ({0})")]
     Unknown(String),
 }
 
 pub type Empty = u8;
 
+// The elements that were removed from the mesh by an operation.
+// Can be used to update any side tables that are indexed by these IDs.
+#[derive(Default, Clone, Debug)]
+pub struct Removed<VertID, EdgeID, FaceID> {
+    pub verts: Vec<VertID>,
+    pub edges: Vec<EdgeID>,
+    pub faces: Vec<FaceID>,
+}
+
+// Options for constructing a DCEL from a list of faces (see `from_faces_with`).
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ConstructionOptions {
+    // Accept meshes that consist of multiple (connected) components, instead of returning `MeshError::NotConnected`.
+    pub allow_disconnected: bool,
+    // Duplicate every non-manifold vertex once per fan of faces (see `split_non_manifold_vertices`), instead of returning `MeshError::NonManifoldVertex`.
+    // The duplicates are mapped from the vertex indices after the existing ones, in the order of `split_non_manifold_vertices` (see `ConstructionReport::vertex_origin`).
+    pub split_non_manifold_vertices: bool,
+}
+
+// The changes that were made to the input faces while constructing a DCEL (see `from_faces_with`).
+#[derive(Default, Clone, Debug)]
+pub struct ConstructionReport {
+    // The indices of the input faces that were flipped to orient the mesh consistently (see `orient_faces`).
+    pub flipped_faces: Vec<usize>,
+    // For every vertex index (including the duplicates that were added by `split_non_manifold_vertices`), the input vertex index it originates from.
+    pub vertex_origin: Vec<usize>,
+}
+
+// Policy for the data of the elements that are created (or merged) by the editing operations (see e.g. `split_edge_with`).
+// The default implementations give new vertices and edges default data, new faces a copy of the face they are split from, and merged faces the data of the surviving face.
+pub trait AttributePolicy<V: Default, E: Default, F: Clone> {
+    // Data of a new vertex at parameter `t` between vertices `a` (t = 0) and `b` (t = 1).
+    fn interpolate_vertex(&self, _a: &V, _b: &V, _t: f64) -> V {
+        V::default()
+    }
+
+    // Data of a new face that is split from the face `parent`.
+    fn split_face_attr(&self, parent: &F) -> F {
+        parent.clone()
+    }
+
+    // Data of a new edge that is split from the edge `parent`.
+    fn split_edge_attr(&self, _parent: &E) -> E {
+        E::default()
+    }
+
+    // Data of a new edge that is not split from an existing edge (e.g. a diagonal).
+    fn new_edge_attr(&self) -> E {
+        E::default()
+    }
+
+    // Data of the face that results from merging the face `removed` into the face `kept`.
+    fn merge_face_attr(&self, kept: &F, _removed: &F) -> F {
+        kept.clone()
+    }
+}
+
+// The policy that is used by the editing operations without `_with`.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct DefaultPolicy;
+
+impl<V: Default, E: Default, F: Clone> AttributePolicy<V, E, F> for DefaultPolicy {}
+
+// The policy that is used by `split_edge`: new faces get default data (instead of a copy of the face they are split from).
+struct DefaultFacePolicy;
+
+impl<V: Default, E: Default, F: Default + Clone> AttributePolicy<V, E, F> for DefaultFacePolicy {
+    fn split_face_attr(&self, _parent: &F) -> F {
+        F::default()
+    }
+}
+
+// Per-edge data that is stored once per pair of twins, keyed by the canonical half-edge (see `Douconel::canonical`).
+// Both half-edges of a pair map to the same value. The mesh is passed to every lookup, so edges that are added to the mesh later are supported.
+#[derive(Clone, Debug)]
+pub struct UndirectedEdgeMap<EdgeID: Key, T> {
+    values: SecondaryMap<EdgeID, T>,
+}
+
+impl<EdgeID: Key, T> Default for UndirectedEdgeMap<EdgeID, T> {
+    fn default() -> Self {
+        Self { values: SecondaryMap::new() }
+    }
+}
+
+impl<EdgeID: Key, T> UndirectedEdgeMap<EdgeID, T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Creates a map with a value for every undirected edge of the given mesh (computed from its canonical half-edge).
+    pub fn from_fn<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        mut f: impl FnMut(EdgeID) -> T,
+    ) -> Self {
+        Self {
+            values: mesh.undirected_edges().into_iter().map(|edge_id| (edge_id, f(edge_id))).collect(),
+        }
+    }
+
+    // Inserts a value for the given edge (and its twin), and returns the previous value.
+    pub fn insert<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        &mut self,
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        id: EdgeID,
+        value: T,
+    ) -> Option<T> {
+        self.values.insert(mesh.canonical(id), value)
+    }
+
+    #[must_use]
+    pub fn get<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        &self,
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        id: EdgeID,
+    ) -> Option<&T> {
+        self.values.get(mesh.canonical(id))
+    }
+
+    pub fn get_mut<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        &mut self,
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        id: EdgeID,
+    ) -> Option<&mut T> {
+        self.values.get_mut(mesh.canonical(id))
+    }
+
+    pub fn remove<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        &mut self,
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        id: EdgeID,
+    ) -> Option<T> {
+        self.values.remove(mesh.canonical(id))
+    }
+
+    #[must_use]
+    pub fn contains_key<VertID: Key, V: Default, E: Default, FaceID: Key, F: Default>(
+        &self,
+        mesh: &Douconel<VertID, V, EdgeID, E, FaceID, F>,
+        id: EdgeID,
+    ) -> bool {
+        self.values.contains_key(mesh.canonical(id))
+    }
+
+    // Returns the number of undirected edges with a value.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // Returns an iterator over the canonical half-edges with their values.
+    pub fn iter(&self) -> impl Iterator<Item = (EdgeID, &T)> {
+        self.values.iter()
+    }
+}
+
 // This is a struct that defines a mesh with vertices, edges, and faces.
 // This mesh is:
-// 1) closed 2-manifold: Each edge corresponds to exactly two faces.
-// 2) connected: There exists a path between any two vertices.
+// 1) 2-manifold (possibly with boundary): Each edge corresponds to one or two faces.
+//    Half-edges on the boundary have no face, and are linked (through next) into boundary loops.
+// 2) connected: There exists a path between any two vertices. (unless constructed with `allow_disconnected`)
 // 3) orientable: There exists a consistent normal for each face.
 // These requirements will be true per construction.
 // We use a doubly connected edge list (DCEL) data structure, also known as the half-edge data structure (HEDS).
@@ -38,12 +242,21 @@ pub struct Douconel<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> {
     pub verts: SlotMap<VertID, V>,
     pub edges: SlotMap<EdgeID, E>,
     pub faces: SlotMap<FaceID, F>,
-    edge_root: SecondaryMap<EdgeID, VertID>,
-    edge_face: SecondaryMap<EdgeID, FaceID>,
-    edge_next: SecondaryMap<EdgeID, EdgeID>,
-    edge_twin: SecondaryMap<EdgeID, EdgeID>,
-    vert_rep: SecondaryMap<VertID, EdgeID>,
-    face_rep: SecondaryMap<FaceID, EdgeID>,
+    pub(crate) edge_root: SecondaryMap<EdgeID, VertID>,
+    pub(crate) edge_face: SecondaryMap<EdgeID, FaceID>,
+    pub(crate) edge_next: SecondaryMap<EdgeID, EdgeID>,
+    pub(crate) edge_twin: SecondaryMap<EdgeID, EdgeID>,
+    pub(crate) vert_rep: SecondaryMap<VertID, EdgeID>,
+    pub(crate) face_rep: SecondaryMap<FaceID, EdgeID>,
+    #[serde(default)]
+    pub(crate) vert_props: Properties<VertID>,
+    #[serde(default)]
+    pub(crate) edge_props: Properties<EdgeID>,
+    #[serde(default)]
+    pub(crate) face_props: Properties<FaceID>,
+    // Not serialized, and not cloned (a clone starts with an empty history).
+    #[serde(skip)]
+    pub(crate) history: History<VertID, V, EdgeID, E, FaceID, F>,
 }
 
 impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
@@ -55,23 +268,313 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
 
     // Adds a vertex to the mesh and returns its ID.
     fn add_vertex(&mut self) -> VertID {
-        self.verts.insert(V::default())
+        self.insert_vertex(V::default())
     }
 
     // Adds an edge to the mesh and returns its ID.
     fn add_edge(&mut self) -> EdgeID {
-        self.edges.insert(E::default())
+        self.insert_edge(E::default())
     }
 
     // Adds a face to the mesh and returns its ID.
     fn add_face(&mut self) -> FaceID {
-        self.faces.insert(F::default())
+        self.insert_face(F::default())
+    }
+
+    // Adds a vertex with the given data to the mesh (and to its property layers) and returns its ID.
+    fn insert_vertex(&mut self, vert: V) -> VertID {
+        let id = self.verts_mut().insert(vert);
+        self.vert_props_mut().insert_key(id);
+        id
+    }
+
+    // Adds an edge with the given data to the mesh (and to its property layers) and returns its ID.
+    fn insert_edge(&mut self, edge: E) -> EdgeID {
+        let id = self.edges_mut().insert(edge);
+        self.edge_props_mut().insert_key(id);
+        id
+    }
+
+    // Adds a face with the given data to the mesh (and to its property layers) and returns its ID.
+    fn insert_face(&mut self, face: F) -> FaceID {
+        let id = self.faces_mut().insert(face);
+        self.face_props_mut().insert_key(id);
+        id
+    }
+
+    // Removes a vertex (and its properties) from the mesh and returns its data.
+    fn remove_vertex(&mut self, id: VertID) -> Option<V> {
+        record(&mut self.vert_rep, self.history.changes().map(|changes| &mut changes.vert_rep), id, None);
+        self.vert_props_mut().remove_key(id);
+        self.verts_mut().remove(id)
+    }
+
+    // Removes an edge (and its properties) from the mesh and returns its data.
+    fn remove_edge(&mut self, id: EdgeID) -> Option<E> {
+        record(&mut self.edge_root, self.history.changes().map(|changes| &mut changes.edge_root), id, None);
+        record(&mut self.edge_face, self.history.changes().map(|changes| &mut changes.edge_face), id, None);
+        record(&mut self.edge_next, self.history.changes().map(|changes| &mut changes.edge_next), id, None);
+        record(&mut self.edge_twin, self.history.changes().map(|changes| &mut changes.edge_twin), id, None);
+        self.edge_props_mut().remove_key(id);
+        self.edges_mut().remove(id)
+    }
+
+    // Removes a face (and its properties) from the mesh and returns its data.
+    fn remove_face(&mut self, id: FaceID) -> Option<F> {
+        record(&mut self.face_rep, self.history.changes().map(|changes| &mut changes.face_rep), id, None);
+        self.face_props_mut().remove_key(id);
+        self.faces_mut().remove(id)
+    }
+
+    // Sets the root of a half-edge. Like all helpers below, this records the change in the open transaction (see `begin`).
+    fn set_root(&mut self, edge_id: EdgeID, vert_id: VertID) {
+        record(
+            &mut self.edge_root,
+            self.history.changes().map(|changes| &mut changes.edge_root),
+            edge_id,
+            Some(vert_id),
+        );
+    }
+
+    // Sets the face of a half-edge (None for a boundary half-edge).
+    fn set_face(&mut self, edge_id: EdgeID, face_id: Option<FaceID>) {
+        record(
+            &mut self.edge_face,
+            self.history.changes().map(|changes| &mut changes.edge_face),
+            edge_id,
+            face_id,
+        );
+    }
+
+    fn set_next(&mut self, edge_id: EdgeID, next_id: EdgeID) {
+        record(
+            &mut self.edge_next,
+            self.history.changes().map(|changes| &mut changes.edge_next),
+            edge_id,
+            Some(next_id),
+        );
+    }
+
+    fn set_twin(&mut self, edge_id: EdgeID, twin_id: EdgeID) {
+        record(
+            &mut self.edge_twin,
+            self.history.changes().map(|changes| &mut changes.edge_twin),
+            edge_id,
+            Some(twin_id),
+        );
+    }
+
+    // Sets the representative (outgoing) half-edge of a vertex.
+    fn set_vert_rep(&mut self, vert_id: VertID, edge_id: EdgeID) {
+        record(
+            &mut self.vert_rep,
+            self.history.changes().map(|changes| &mut changes.vert_rep),
+            vert_id,
+            Some(edge_id),
+        );
+    }
+
+    // Sets the representative half-edge of a face.
+    fn set_face_rep(&mut self, face_id: FaceID, edge_id: EdgeID) {
+        record(
+            &mut self.face_rep,
+            self.history.changes().map(|changes| &mut changes.face_rep),
+            face_id,
+            Some(edge_id),
+        );
+    }
+
+    // Returns the vertices (mutable), after copying them for the open transaction (once per transaction).
+    fn verts_mut(&mut self) -> &mut SlotMap<VertID, V> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_verts(&self.verts);
+        }
+        &mut self.verts
+    }
+
+    // Returns the edges (mutable), after copying them for the open transaction (once per transaction).
+    fn edges_mut(&mut self) -> &mut SlotMap<EdgeID, E> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_edges(&self.edges);
+        }
+        &mut self.edges
+    }
+
+    // Returns the faces (mutable), after copying them for the open transaction (once per transaction).
+    fn faces_mut(&mut self) -> &mut SlotMap<FaceID, F> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_faces(&self.faces);
+        }
+        &mut self.faces
+    }
+
+    // Returns the vertex property layers (mutable), after copying them for the open transaction (once per transaction).
+    pub(crate) fn vert_props_mut(&mut self) -> &mut Properties<VertID> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_vert_props(&self.vert_props);
+        }
+        &mut self.vert_props
+    }
+
+    // Returns the edge property layers (mutable), after copying them for the open transaction (once per transaction).
+    pub(crate) fn edge_props_mut(&mut self) -> &mut Properties<EdgeID> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_edge_props(&self.edge_props);
+        }
+        &mut self.edge_props
+    }
+
+    // Returns the face property layers (mutable), after copying them for the open transaction (once per transaction).
+    pub(crate) fn face_props_mut(&mut self) -> &mut Properties<FaceID> {
+        if let Some(changes) = self.history.changes() {
+            changes.save_face_props(&self.face_props);
+        }
+        &mut self.face_props
+    }
+
+    // Orient a list of faces (each face is a list of vertex indices) consistently.
+    // Faces are visited face by face (through their shared edges), starting from the first face of each component.
+    // A face that is wound inconsistently with its (already visited) neighbor is flipped.
+    // Returns the oriented faces, and the indices of the faces that were flipped.
+    // Returns an error if the faces cannot be oriented consistently (e.g. a Möbius strip).
+    // Edges shared by more than two faces are ignored here (see `MeshError::DuplicateEdge`).
+    pub fn orient_faces(faces: &[Vec<usize>]) -> Result<(Vec<Vec<usize>>, Vec<usize>), MeshError<VertID, EdgeID, FaceID>> {
+        // Need mapping between (undirected) edges and the faces that contain them, including the direction in which they traverse the edge.
+        let mut edge_to_faces = HashMap::<(usize, usize), Vec<(usize, bool)>>::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                edge_to_faces.entry((a.min(b), a.max(b))).or_default().push((face_index, a < b));
+            }
+        }
+
+        // For each face, whether it is flipped (None if not yet visited).
+        let mut flipped = vec![None; faces.len()];
+        for seed in 0..faces.len() {
+            if flipped[seed].is_some() {
+                continue;
+            }
+            flipped[seed] = Some(false);
+            let mut queue = VecDeque::from([seed]);
+            while let Some(face_index) = queue.pop_front() {
+                let face = &faces[face_index];
+                let face_flipped = flipped[face_index] == Some(true);
+                for i in 0..face.len() {
+                    let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                    let incident = &edge_to_faces[&(a.min(b), a.max(b))];
+                    if incident.len() != 2 {
+                        continue;
+                    }
+                    // The direction of this edge in this face (after flipping).
+                    let direction = (a < b) != face_flipped;
+                    for &(neighbor_index, neighbor_direction) in incident {
+                        if neighbor_index == face_index {
+                            continue;
+                        }
+                        // The neighbor must traverse the edge in the opposite direction.
+                        let neighbor_flipped = neighbor_direction == direction;
+                        match flipped[neighbor_index] {
+                            None => {
+                                flipped[neighbor_index] = Some(neighbor_flipped);
+                                queue.push_back(neighbor_index);
+                            }
+                            Some(already_flipped) if already_flipped != neighbor_flipped => return Err(MeshError::NotOrientable),
+                            Some(_) => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        let oriented_faces = faces
+            .iter()
+            .zip(&flipped)
+            .map(|(face, &face_flipped)| {
+                if face_flipped == Some(true) {
+                    face.iter().rev().copied().collect_vec()
+                } else {
+                    face.clone()
+                }
+            })
+            .collect_vec();
+        let flipped_faces = flipped.into_iter().positions(|face_flipped| face_flipped == Some(true)).collect_vec();
+
+        Ok((oriented_faces, flipped_faces))
+    }
+
+    // Split the non-manifold vertices of a list of faces (each face is a list of vertex indices).
+    // A vertex is non-manifold if its incident faces form more than one fan, i.e. they are not all connected through edges incident to the vertex (two cones touching at their apex).
+    // The first fan (the one with the lowest face index) keeps the vertex; every other fan gets a new vertex, appended after the existing vertex indices.
+    // Returns the new faces, and for each (new) vertex index the original vertex index.
+    #[must_use]
+    pub fn split_non_manifold_vertices(faces: &[Vec<usize>]) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let nr_verts = faces.iter().flatten().max().map_or(0, |&max| max + 1);
+        let mut origin = (0..nr_verts).collect_vec();
+
+        // Need mapping between vertices and the faces that contain them, and between (undirected) edges and the faces that contain them.
+        let mut vert_to_faces = vec![vec![]; nr_verts];
+        let mut edge_to_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                vert_to_faces[a].push(face_index);
+                edge_to_faces.entry((a.min(b), a.max(b))).or_default().push(face_index);
+            }
+        }
+
+        let mut split_faces = faces.to_vec();
+        for (vert, incident_faces) in vert_to_faces.iter().enumerate() {
+            // The faces around this vertex are neighbors if they share an edge that is incident to this vertex.
+            let neighbors = |face_index: usize| {
+                let face = &faces[face_index];
+                face.iter()
+                    .circular_tuple_windows()
+                    .filter(|&(&a, &b)| a == vert || b == vert)
+                    .flat_map(|(&a, &b)| edge_to_faces[&(a.min(b), a.max(b))].iter().copied())
+                    .filter(|&neighbor_index| neighbor_index != face_index)
+                    .collect_vec()
+            };
+            let fans = hutspot::graph::find_ccs(&incident_faces.iter().copied().unique().collect_vec(), neighbors)
+                .into_iter()
+                .map(|fan| fan.into_iter().sorted().collect_vec())
+                .sorted()
+                .collect_vec();
+
+            for fan in fans.into_iter().skip(1) {
+                let new_vert = origin.len();
+                origin.push(vert);
+                for face_index in fan {
+                    for corner in &mut split_faces[face_index] {
+                        if *corner == vert {
+                            *corner = new_vert;
+                        }
+                    }
+                }
+            }
+        }
+
+        (split_faces, origin)
     }
 
     // Construct a DCEL from a list of faces, where each face is a list of vertex indices.
-    pub fn from_faces(faces: &[Vec<usize>]) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), MeshError<VertID>> {
+    // The faces are oriented consistently first (see `orient_faces`); use `from_faces_with` to find out which faces were flipped.
+    pub fn from_faces(faces: &[Vec<usize>]) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        Self::from_faces_with(faces, ConstructionOptions::default()).map(|(mesh, vert_map, face_map, _)| (mesh, vert_map, face_map))
+    }
+
+    // Construct a DCEL from a list of faces, with the given construction options.
+    // Also returns a report of the changes that were made to the input faces (e.g., the faces that were flipped).
+    pub fn from_faces_with(
+        faces: &[Vec<usize>],
+        options: ConstructionOptions,
+    ) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>, ConstructionReport), MeshError<VertID, EdgeID, FaceID>> {
         let mut mesh = Self::empty();
 
+        // 0. Make sure the faces are oriented consistently.
+        //      if `split_non_manifold_vertices` is set, first split the non-manifold vertices (see `split_non_manifold_vertices`)
+        //      flip every face that is wound inconsistently with its neighbors (see `orient_faces`)
+        //      return error if the faces are not orientable
+        //
         // 1. Create the vertices.
         //      trivial; get all unique input vertices (from the faces), and create a vertex for each of them
         //
@@ -92,11 +595,27 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         //
         // 3. Assign twins.
         //      trivial; just assign THE edge that has the same endpoints, but swapped (just requires some bookkeeping)
-        //      return error if no such edge exists
+        //      if no such edge exists, the edge is on the boundary: create a boundary (half)edge without a face as its twin
+        //
+        // 4. Link the boundary (half)edges.
+        //      each boundary edge (v1, v0) is followed by THE boundary edge that starts in v0
+        //      return error if a vertex has multiple outgoing boundary edges (not a 2-manifold)
+        //
+        // 5. Make sure every vertex has a single fan of faces.
+        //      the outgoing edges of a vertex (twin->next->...) must visit all edges with this vertex as root
+        //      return error if a vertex has multiple fans (not a 2-manifold)
+        //
+        // 6. Make sure the mesh is connected.
+        //      return error if the mesh has multiple components (unless `allow_disconnected` is set)
         //
 
-        // 0. TODO: Make sure the faces are orientable.
-        // for each face, choose an orientation, then make sure the neighboring faces have the same orientation.
+        // 0. Make sure the faces are oriented consistently.
+        let (faces, vertex_origin) = if options.split_non_manifold_vertices {
+            Self::split_non_manifold_vertices(faces)
+        } else {
+            (faces.to_vec(), (0..faces.iter().flatten().max().map_or(0, |&max| max + 1)).collect_vec())
+        };
+        let (faces, flipped_faces) = Self::orient_faces(&faces)?;
 
         // 1. Create the vertices.
         // Need mapping between original indices, and new pointers
@@ -116,61 +635,84 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
             let face_id = mesh.add_face();
             face_pointers.insert(inp_face_id, face_id);
 
+            // Every input vertex has a pointer (created in step 1).
+            let face_verts = inp_face_verts
+                .iter()
+                .map(|inp_vert_id| vertex_pointers.get_by_left(inp_vert_id).copied().unwrap())
+                .collect_vec();
             let mut edge_ids = vec![];
-            for i in 0..inp_face_verts.len() {
-                let inp_start_vertex = inp_face_verts[i];
-                let inp_end_vertex = inp_face_verts[(i + 1) % inp_face_verts.len()];
-                if let (Some(&start_vertex), Some(&end_vertex)) = (vertex_pointers.get_by_left(&inp_start_vertex), vertex_pointers.get_by_left(&inp_end_vertex))
-                {
-                    let edge_id = mesh.add_edge();
-                    let already_exists = endpoints_to_edges.insert((start_vertex, end_vertex), edge_id).is_some();
-                    if already_exists {
-                        return Err(MeshError::DuplicateEdge(start_vertex, end_vertex));
-                    }
-                    edge_ids.push(edge_id);
-                    mesh.face_rep.insert(face_id, edge_id);
-                    mesh.vert_rep.insert(start_vertex, edge_id);
-                    mesh.edge_root.insert(edge_id, start_vertex);
-                    mesh.edge_face.insert(edge_id, face_id);
-                } else {
-                    return Err(MeshError::Unknown(format!(
-                        "V:{inp_start_vertex} or V:{inp_end_vertex} does not have a vertex pointer"
-                    )));
+            for (start_vertex, end_vertex) in face_verts.into_iter().circular_tuple_windows() {
+                let edge_id = mesh.add_edge();
+                let already_exists = endpoints_to_edges.insert((start_vertex, end_vertex), edge_id).is_some();
+                if already_exists {
+                    return Err(MeshError::DuplicateEdge(start_vertex, end_vertex));
                 }
+                edge_ids.push(edge_id);
+                mesh.set_face_rep(face_id, edge_id);
+                mesh.set_vert_rep(start_vertex, edge_id);
+                mesh.set_root(edge_id, start_vertex);
+                mesh.set_face(edge_id, Some(face_id));
             }
 
             // Linking each edge to its next edge in the face
             for edge_index in 0..edge_ids.len() {
-                mesh.edge_next.insert(edge_ids[edge_index], edge_ids[(edge_index + 1) % edge_ids.len()]);
+                mesh.set_next(edge_ids[edge_index], edge_ids[(edge_index + 1) % edge_ids.len()]);
             }
         }
 
         // 3. Assign twins.
+        // Need mapping between roots and boundary edges for later use (linking the boundary).
+        let mut root_to_boundary = HashMap::<VertID, EdgeID>::new();
         for (&(vert_a, vert_b), &edge_id) in &endpoints_to_edges {
             // Retrieve the twin edge
             if let Some(&twin_id) = endpoints_to_edges.get(&(vert_b, vert_a)) {
                 // Assign twins
-                mesh.edge_twin.insert(edge_id, twin_id);
-                mesh.edge_twin.insert(twin_id, edge_id);
+                mesh.set_twin(edge_id, twin_id);
+                mesh.set_twin(twin_id, edge_id);
             } else {
-                return Err(MeshError::NoTwin(vert_a, vert_b));
+                // Create a boundary edge (without face) as twin
+                let twin_id = mesh.add_edge();
+                mesh.set_root(twin_id, vert_b);
+                mesh.set_twin(edge_id, twin_id);
+                mesh.set_twin(twin_id, edge_id);
+                if root_to_boundary.insert(vert_b, twin_id).is_some() {
+                    return Err(MeshError::NonManifoldVertex(vert_b));
+                }
             }
         }
 
-        // 4. Make sure the mesh is connected.
-        if !mesh.is_connected() {
-            return Err(MeshError::NotConnected);
+        // 4. Link the boundary edges.
+        // A boundary edge without a next boundary edge ends in a vertex that is not a 2-manifold.
+        for &edge_id in root_to_boundary.values() {
+            let toor = mesh.toor(edge_id);
+            let Some(&next_id) = root_to_boundary.get(&toor) else {
+                return Err(MeshError::NonManifoldVertex(toor));
+            };
+            mesh.set_next(edge_id, next_id);
+        }
+
+        // 5. Make sure every vertex has a single fan of faces.
+        let mut nr_outgoing = HashMap::<VertID, usize>::new();
+        for &vert_id in mesh.edge_root.values() {
+            *nr_outgoing.entry(vert_id).or_default() += 1;
+        }
+        for (vert_id, nr) in nr_outgoing {
+            if mesh.outgoing_iter(vert_id).count() != nr {
+                return Err(MeshError::NonManifoldVertex(vert_id));
+            }
         }
 
-        // 5. Make sure the mesh is orientable.
-        // TODO:
+        // 6. Make sure the mesh is connected.
+        if !options.allow_disconnected && !mesh.is_connected() {
+            return Err(MeshError::NotConnected);
+        }
 
         // Assert that all elements have their required properties set.
         mesh.assert_properties();
         mesh.assert_references();
         mesh.assert_invariants();
 
-        Ok((mesh, vertex_pointers, face_pointers))
+        Ok((mesh, vertex_pointers, face_pointers, ConstructionReport { flipped_faces, vertex_origin }))
     }
 
     // Asserts that all elements have their required properties set.
@@ -178,9 +720,13 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     pub fn assert_properties(&self) {
         for edge_id in self.edge_ids() {
             assert!(self.edge_root.contains_key(edge_id), "{edge_id:?} has no root");
-            assert!(self.edge_face.contains_key(edge_id), "{edge_id:?} has no face");
             assert!(self.edge_next.contains_key(edge_id), "{edge_id:?} has no next");
             assert!(self.edge_twin.contains_key(edge_id), "{edge_id:?} has no twin");
+            // Only boundary edges have no face, and their twin must have a face.
+            assert!(
+                self.edge_face.contains_key(edge_id) || self.edge_face.contains_key(self.twin(edge_id)),
+                "{edge_id:?} and its twin have no face"
+            );
         }
         for vert_id in self.vert_ids() {
             assert!(self.vert_rep.contains_key(vert_id), "{vert_id:?} has no vrep");
@@ -197,8 +743,9 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
             let root_id = self.root(edge_id);
             assert!(self.verts.contains_key(root_id), "{edge_id:?} has non-existing root ({root_id:?})");
 
-            let face_id = self.face(edge_id);
-            assert!(self.faces.contains_key(face_id), "{edge_id:?} has non-existing face ({face_id:?})");
+            if let Some(&face_id) = self.edge_face.get(edge_id) {
+                assert!(self.faces.contains_key(face_id), "{edge_id:?} has non-existing face ({face_id:?})");
+            }
 
             let next_id = self.next(edge_id);
             assert!(self.edges.contains_key(next_id), "{edge_id:?} has non-existing next ({next_id:?})");
@@ -232,15 +779,14 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         // this->next->face == this->face
         for edge_id in self.edge_ids() {
             assert!(
-                self.face(self.next(edge_id)) == self.face(edge_id),
+                self.edge_face.get(self.next(edge_id)) == self.edge_face.get(edge_id),
                 "{edge_id:?}: [this->next->face == this->face] violated"
             );
         }
         // this->next->...->next == this
-        const MAX_FACE_SIZE: usize = 10;
         for edge_id in self.edge_ids() {
             let mut next_id = edge_id;
-            for _ in 0..MAX_FACE_SIZE {
+            for _ in 0..self.nr_edges() {
                 next_id = self.next(next_id);
                 if next_id == edge_id {
                     break;
@@ -285,6 +831,15 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         self.edge_twin.get(id).copied().unwrap_or_else(|| panic!("{id:?} has no twin"))
     }
 
+    // Returns the canonical half-edge of the given edge: the smallest ID of the edge and its twin.
+    // Both half-edges of a pair have the same canonical half-edge.
+    // Panics if the edge has no twin defined.
+    #[inline]
+    #[must_use]
+    pub fn canonical(&self, id: EdgeID) -> EdgeID {
+        id.min(self.twin(id))
+    }
+
     // Returns the next edge of the given edge.
     // Panics if the edge has no next defined or if the next does not exist.
     #[inline]
@@ -293,20 +848,26 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         self.edge_next.get(id).copied().unwrap_or_else(|| panic!("{id:?} has no next"))
     }
 
-    #[inline]
+    // Returns the previous edge of the given edge (the edge whose next is the given edge).
+    // Panics if any of the edges in the cycle have no next defined.
     #[must_use]
-    pub fn nexts(&self, id: EdgeID) -> Vec<EdgeID> {
-        let mut nexts = vec![];
+    pub fn prev(&self, id: EdgeID) -> EdgeID {
         let mut cur = id;
         loop {
-            cur = self.next(cur);
-            if cur == id {
-                return nexts;
+            let next = self.next(cur);
+            if next == id {
+                return cur;
             }
-            nexts.push(cur);
+            cur = next;
         }
     }
 
+    #[inline]
+    #[must_use]
+    pub fn nexts(&self, id: EdgeID) -> Vec<EdgeID> {
+        self.nexts_iter(id).collect()
+    }
+
     // Returns the four edges around a given edge.
     #[inline]
     #[must_use]
@@ -320,7 +881,7 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     }
 
     // Returns the face of the given edge.
-    // Panics if the edge has no face defined or if the face does not exist.
+    // Panics if the edge has no face defined (boundary edge) or if the face does not exist.
     #[inline]
     #[must_use]
     pub fn face(&self, id: EdgeID) -> FaceID {
@@ -339,51 +900,47 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     #[inline]
     #[must_use]
     pub fn corners(&self, id: FaceID) -> Vec<VertID> {
-        self.edges(id).into_iter().map(|edge_id| self.root(edge_id)).collect()
+        self.corners_iter(id).collect()
     }
 
     // Returns the outgoing edges of a given vertex. (clockwise order)
     #[inline]
     #[must_use]
     pub fn outgoing(&self, id: VertID) -> Vec<EdgeID> {
-        let mut edges = vec![self.vrep(id)];
-        loop {
-            let next_of_twin = self.next(self.twin(edges.last().copied().unwrap()));
-            if edges.contains(&next_of_twin) {
-                return edges;
-            }
-            edges.push(next_of_twin);
-        }
+        self.outgoing_iter(id).collect()
     }
 
     // Returns the edges of a given face. (anticlockwise order)
     #[inline]
     #[must_use]
     pub fn edges(&self, id: FaceID) -> Vec<EdgeID> {
-        [vec![self.frep(id)], self.nexts(self.frep(id))].concat()
+        self.edges_iter(id).collect()
     }
 
     // Returns the faces around a given vertex. (clockwise order)
+    // Boundary edges are skipped, as they have no face.
     #[inline]
     #[must_use]
     pub fn star(&self, id: VertID) -> Vec<FaceID> {
-        self.outgoing(id).iter().map(|&edge_id| self.face(edge_id)).collect()
+        self.star_iter(id).collect()
     }
 
     // Returns the faces around a given edge.
+    // Panics if the edge is on the boundary.
     #[inline]
     #[must_use]
     pub fn faces(&self, id: EdgeID) -> [FaceID; 2] {
         [self.face(id), self.face(self.twin(id))]
     }
 
-    // Returns the face with given vertices.
+    // Returns the face with given vertices. Returns None if there is no such face (or if any of the vertices does not exist).
     #[inline]
     #[must_use]
     pub fn face_with_verts(&self, verts: &[VertID]) -> Option<FaceID> {
-        self.star(verts[0])
+        self.try_star(*verts.first()?)
+            .ok()?
             .into_iter()
-            .find(|&face_id| verts.iter().all(|&vert_id| self.star(vert_id).contains(&face_id)))
+            .find(|&face_id| verts.iter().all(|&vert_id| self.try_star(vert_id).is_ok_and(|star| star.contains(&face_id))))
     }
 
     // Returns the two edges of a given face that are connected to the given vertex.
@@ -430,14 +987,9 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     #[inline]
     #[must_use]
     pub fn edge_between_verts(&self, id_a: VertID, id_b: VertID) -> Option<(EdgeID, EdgeID)> {
-        for &edge_a_id in &self.outgoing(id_a) {
-            for &edge_b_id in &self.outgoing(id_b) {
-                if self.twin(edge_a_id) == edge_b_id {
-                    return Some((edge_a_id, edge_b_id));
-                }
-            }
-        }
-        None
+        self.outgoing_iter(id_a)
+            .find(|&edge_a_id| self.toor(edge_a_id) == id_b)
+            .map(|edge_a_id| (edge_a_id, self.twin(edge_a_id)))
     }
 
     // Returns the edge between the two faces. Returns None if the faces do not share an edge.
@@ -458,13 +1010,271 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     // Returns the neighbors of a given vertex.
     #[must_use]
     pub fn vneighbors(&self, id: VertID) -> Vec<VertID> {
-        self.outgoing(id).iter().map(|&edge_id| self.root(self.twin(edge_id))).collect()
+        self.vneighbors_iter(id).collect()
     }
 
     // Returns the (edge-wise) neighbors of a given face.
+    // Boundary edges are skipped, as they have no face.
     #[must_use]
     pub fn fneighbors(&self, id: FaceID) -> Vec<FaceID> {
-        self.edges(id).into_iter().map(|edge_id| self.face(self.twin(edge_id))).collect()
+        self.fneighbors_iter(id).collect()
+    }
+
+    // Non-panicking variants of the accessors above.
+    // Every element that is visited is checked: return `MeshError::InvalidVertex`, `MeshError::InvalidEdge`, or `MeshError::InvalidFace` if an element does not exist (e.g. a stale ID of a removed element),
+    // or if one of its references is missing or dangling (e.g. a corrupt mesh). A cycle of next pointers (or of outgoing edges) that does not close is reported on the edge it starts from.
+
+    fn check_vert(&self, id: VertID) -> Result<VertID, MeshError<VertID, EdgeID, FaceID>> {
+        if self.verts.contains_key(id) {
+            Ok(id)
+        } else {
+            Err(MeshError::InvalidVertex(id))
+        }
+    }
+
+    fn check_edge(&self, id: EdgeID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        if self.edges.contains_key(id) {
+            Ok(id)
+        } else {
+            Err(MeshError::InvalidEdge(id))
+        }
+    }
+
+    fn check_face(&self, id: FaceID) -> Result<FaceID, MeshError<VertID, EdgeID, FaceID>> {
+        if self.faces.contains_key(id) {
+            Ok(id)
+        } else {
+            Err(MeshError::InvalidFace(id))
+        }
+    }
+
+    pub fn try_vrep(&self, id: VertID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        let rep = self.vert_rep.get(self.check_vert(id)?).copied().ok_or(MeshError::InvalidVertex(id))?;
+        self.check_edge(rep)
+    }
+
+    pub fn try_frep(&self, id: FaceID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        let rep = self.face_rep.get(self.check_face(id)?).copied().ok_or(MeshError::InvalidFace(id))?;
+        self.check_edge(rep)
+    }
+
+    pub fn try_root(&self, id: EdgeID) -> Result<VertID, MeshError<VertID, EdgeID, FaceID>> {
+        let root = self.edge_root.get(self.check_edge(id)?).copied().ok_or(MeshError::InvalidEdge(id))?;
+        self.check_vert(root)
+    }
+
+    pub fn try_toor(&self, id: EdgeID) -> Result<VertID, MeshError<VertID, EdgeID, FaceID>> {
+        self.try_root(self.try_twin(id)?)
+    }
+
+    pub fn try_twin(&self, id: EdgeID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        let twin = self.edge_twin.get(self.check_edge(id)?).copied().ok_or(MeshError::InvalidEdge(id))?;
+        self.check_edge(twin)
+    }
+
+    pub fn try_next(&self, id: EdgeID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        let next = self.edge_next.get(self.check_edge(id)?).copied().ok_or(MeshError::InvalidEdge(id))?;
+        self.check_edge(next)
+    }
+
+    pub fn try_prev(&self, id: EdgeID) -> Result<EdgeID, MeshError<VertID, EdgeID, FaceID>> {
+        Ok(self.try_cycle(id)?.last().copied().unwrap_or(id))
+    }
+
+    // Returns the edges following the given edge in its cycle, excluding the given edge itself (see `nexts`).
+    pub fn try_nexts(&self, id: EdgeID) -> Result<Vec<EdgeID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.try_cycle(id)
+    }
+
+    // Walks the next pointers from the given edge until it is reached again, and returns the visited edges (excluding the given edge).
+    fn try_cycle(&self, id: EdgeID) -> Result<Vec<EdgeID>, MeshError<VertID, EdgeID, FaceID>> {
+        let mut cycle = vec![];
+        let mut cur = self.try_next(id)?;
+        while cur != id {
+            if cycle.len() >= self.nr_edges() {
+                return Err(MeshError::InvalidEdge(id));
+            }
+            cycle.push(cur);
+            cur = self.try_next(cur)?;
+        }
+        Ok(cycle)
+    }
+
+    pub fn try_quad(&self, id: EdgeID) -> Result<[EdgeID; 4], MeshError<VertID, EdgeID, FaceID>> {
+        let edge0 = self.try_next(id)?;
+        let edge1 = self.try_next(edge0)?;
+        let edge2 = self.try_next(self.try_twin(id)?)?;
+        let edge3 = self.try_next(edge2)?;
+        Ok([edge0, edge1, edge2, edge3])
+    }
+
+    pub fn try_endpoints(&self, id: EdgeID) -> Result<(VertID, VertID), MeshError<VertID, EdgeID, FaceID>> {
+        Ok((self.try_root(id)?, self.try_toor(id)?))
+    }
+
+    // Also returns `MeshError::BoundaryEdge` if the edge has no face.
+    pub fn try_face(&self, id: EdgeID) -> Result<FaceID, MeshError<VertID, EdgeID, FaceID>> {
+        if let Some(&face_id) = self.edge_face.get(self.check_edge(id)?) {
+            self.check_face(face_id)
+        } else {
+            let (u, v) = self.try_endpoints(id)?;
+            Err(MeshError::BoundaryEdge(u, v))
+        }
+    }
+
+    // Also returns `MeshError::BoundaryEdge` if the edge is on the boundary.
+    pub fn try_faces(&self, id: EdgeID) -> Result<[FaceID; 2], MeshError<VertID, EdgeID, FaceID>> {
+        Ok([self.try_face(id)?, self.try_face(self.try_twin(id)?)?])
+    }
+
+    pub fn try_corners(&self, id: FaceID) -> Result<Vec<VertID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.try_edges(id)?.into_iter().map(|edge_id| self.try_root(edge_id)).collect()
+    }
+
+    pub fn try_edges(&self, id: FaceID) -> Result<Vec<EdgeID>, MeshError<VertID, EdgeID, FaceID>> {
+        let rep = self.try_frep(id)?;
+        let mut edges = vec![rep];
+        edges.extend(self.try_cycle(rep)?);
+        Ok(edges)
+    }
+
+    pub fn try_fneighbors(&self, id: FaceID) -> Result<Vec<FaceID>, MeshError<VertID, EdgeID, FaceID>> {
+        let mut neighbors = vec![];
+        for edge_id in self.try_edges(id)? {
+            if let Some(&face_id) = self.edge_face.get(self.try_twin(edge_id)?) {
+                neighbors.push(self.check_face(face_id)?);
+            }
+        }
+        Ok(neighbors)
+    }
+
+    pub fn try_outgoing(&self, id: VertID) -> Result<Vec<EdgeID>, MeshError<VertID, EdgeID, FaceID>> {
+        let rep = self.try_vrep(id)?;
+        let mut outgoing = vec![rep];
+        let mut cur = self.try_next(self.try_twin(rep)?)?;
+        while cur != rep {
+            if outgoing.len() >= self.nr_edges() {
+                return Err(MeshError::InvalidEdge(rep));
+            }
+            outgoing.push(cur);
+            cur = self.try_next(self.try_twin(cur)?)?;
+        }
+        Ok(outgoing)
+    }
+
+    pub fn try_star(&self, id: VertID) -> Result<Vec<FaceID>, MeshError<VertID, EdgeID, FaceID>> {
+        let mut star = vec![];
+        for edge_id in self.try_outgoing(id)? {
+            if let Some(&face_id) = self.edge_face.get(edge_id) {
+                star.push(self.check_face(face_id)?);
+            }
+        }
+        Ok(star)
+    }
+
+    pub fn try_vneighbors(&self, id: VertID) -> Result<Vec<VertID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.try_outgoing(id)?.into_iter().map(|edge_id| self.try_toor(edge_id)).collect()
+    }
+
+    // Returns true if the given (half)edge is a boundary edge (it has no face).
+    #[inline]
+    #[must_use]
+    pub fn is_boundary(&self, id: EdgeID) -> bool {
+        !self.edge_face.contains_key(id)
+    }
+
+    // Returns true if the given edge or its twin is a boundary edge.
+    #[inline]
+    #[must_use]
+    pub fn is_boundary_edge(&self, id: EdgeID) -> bool {
+        self.is_boundary(id) || self.is_boundary(self.twin(id))
+    }
+
+    // Returns true if the given vertex has an outgoing boundary edge.
+    #[must_use]
+    pub fn is_boundary_vertex(&self, id: VertID) -> bool {
+        self.outgoing_iter(id).any(|edge_id| self.is_boundary(edge_id))
+    }
+
+    // Returns the boundary loops of the mesh, each loop is a sequence of boundary edges (linked through next).
+    #[must_use]
+    pub fn boundary_loops(&self) -> Vec<Vec<EdgeID>> {
+        let mut visited = HashSet::new();
+        let mut loops = vec![];
+        for edge_id in self.edges.keys().filter(|&edge_id| self.is_boundary(edge_id)) {
+            if visited.contains(&edge_id) {
+                continue;
+            }
+            let boundary_loop = [vec![edge_id], self.nexts(edge_id)].concat();
+            visited.extend(boundary_loop.iter().copied());
+            loops.push(boundary_loop);
+        }
+        loops
+    }
+
+    // Returns true if the given face is a quad (it has four edges).
+    #[must_use]
+    pub fn is_quad(&self, id: FaceID) -> bool {
+        self.edges_iter(id).count() == 4
+    }
+
+    // Returns the valence of a given vertex (its number of outgoing edges).
+    #[must_use]
+    pub fn valence(&self, id: VertID) -> usize {
+        self.outgoing_iter(id).count()
+    }
+
+    // Returns true if the given vertex is regular in a quad mesh: it is not on the boundary, it has valence 4, and all faces around it are quads.
+    #[must_use]
+    pub fn is_regular_quad_vertex(&self, id: VertID) -> bool {
+        !self.is_boundary_vertex(id) && self.valence(id) == 4 && self.star_iter(id).all(|face_id| self.is_quad(face_id))
+    }
+
+    // Returns the edge loop through the given edge (in a quad mesh).
+    // The loop continues through regular vertices (see `is_regular_quad_vertex`) by taking the opposite outgoing edge, and stops at irregular vertices or when it closes.
+    // The edges are ordered head to tail, in the direction of the given edge.
+    #[must_use]
+    pub fn edge_loop(&self, id: EdgeID) -> Vec<EdgeID> {
+        // At the endpoint of a quad edge a->b, the opposite outgoing edge is next(twin(next(a->b))).
+        self.trace(id, |edge_id| {
+            self.is_regular_quad_vertex(self.toor(edge_id))
+                .then(|| self.next(self.twin(self.next(edge_id))))
+        })
+    }
+
+    // Returns the edge ring through the given edge (in a quad mesh).
+    // The ring continues across quads by taking the opposite edge of the face, and stops at non-quad faces, at the boundary, or when it closes.
+    // The edges are ordered as the faces they cross, and all point in the direction of the given edge.
+    #[must_use]
+    pub fn edge_ring(&self, id: EdgeID) -> Vec<EdgeID> {
+        self.trace(id, |edge_id| {
+            (!self.is_boundary(edge_id) && self.is_quad(self.face(edge_id))).then(|| self.twin(self.next(self.next(edge_id))))
+        })
+    }
+
+    // Traces a path of edges through the given edge, in both directions, using `step` to go from one edge to the next.
+    // The backward direction is traced from the twin of the given edge (and its edges are reversed).
+    fn trace(&self, id: EdgeID, step: impl Fn(EdgeID) -> Option<EdgeID>) -> Vec<EdgeID> {
+        let walk = |start: EdgeID| {
+            let mut path = vec![start];
+            let mut visited = HashSet::from([start]);
+            let mut cur = start;
+            while let Some(next) = step(cur) {
+                if !visited.insert(next) {
+                    return (path, true);
+                }
+                path.push(next);
+                cur = next;
+            }
+            (path, false)
+        };
+
+        let (forward, closed) = walk(id);
+        if closed {
+            return forward;
+        }
+        let (backward, _) = walk(self.twin(id));
+        backward.into_iter().skip(1).rev().map(|edge_id| self.twin(edge_id)).chain(forward).collect()
     }
 
     // Returns the number of vertices in the mesh.
@@ -495,6 +1305,12 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         self.edges.keys().collect()
     }
 
+    // Returns one (canonical) half-edge per pair of twins.
+    #[must_use]
+    pub fn undirected_edges(&self) -> Vec<EdgeID> {
+        self.edges.keys().filter(|&edge_id| self.canonical(edge_id) == edge_id).collect()
+    }
+
     #[must_use]
     pub fn face_ids(&self) -> Vec<FaceID> {
         self.faces.keys().collect()
@@ -503,19 +1319,53 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     // Return `n` random vertices.
     #[must_use]
     pub fn random_verts(&self, n: usize) -> Vec<VertID> {
-        self.verts.keys().choose_multiple(&mut rand::rng(), n)
+        self.random_verts_with(n, &mut rand::rng())
+    }
+
+    // Return `n` random vertices, drawn from the given random number generator. (reproducible with a seeded generator)
+    pub fn random_verts_with(&self, n: usize, rng: &mut impl Rng) -> Vec<VertID> {
+        self.verts.keys().choose_multiple(rng, n)
     }
 
     // Return `n` random edges.
     #[must_use]
     pub fn random_edges(&self, n: usize) -> Vec<EdgeID> {
-        self.edges.keys().choose_multiple(&mut rand::rng(), n)
+        self.random_edges_with(n, &mut rand::rng())
+    }
+
+    // Return `n` random edges, drawn from the given random number generator. (reproducible with a seeded generator)
+    pub fn random_edges_with(&self, n: usize, rng: &mut impl Rng) -> Vec<EdgeID> {
+        self.edges.keys().choose_multiple(rng, n)
     }
 
     // Return `n` random faces.
     #[must_use]
     pub fn random_faces(&self, n: usize) -> Vec<FaceID> {
-        self.faces.keys().choose_multiple(&mut rand::rng(), n)
+        self.random_faces_with(n, &mut rand::rng())
+    }
+
+    // Return `n` random faces, drawn from the given random number generator. (reproducible with a seeded generator)
+    pub fn random_faces_with(&self, n: usize, rng: &mut impl Rng) -> Vec<FaceID> {
+        self.faces.keys().choose_multiple(rng, n)
+    }
+
+    // Return a random walk of `steps` steps on the vertex graph, starting at `start`. Every step moves to a uniformly chosen neighbor.
+    // The walk contains `steps + 1` vertices (including `start`).
+    #[must_use]
+    pub fn random_walk(&self, start: VertID, steps: usize) -> Vec<VertID> {
+        self.random_walk_with(start, steps, &mut rand::rng())
+    }
+
+    // Return a random walk (see `random_walk`), drawn from the given random number generator. (reproducible with a seeded generator)
+    pub fn random_walk_with(&self, start: VertID, steps: usize, rng: &mut impl Rng) -> Vec<VertID> {
+        let mut walk = Vec::with_capacity(steps + 1);
+        walk.push(start);
+        let mut cur = start;
+        for _ in 0..steps {
+            cur = self.vneighbors_iter(cur).choose(rng).unwrap_or(cur);
+            walk.push(cur);
+        }
+        walk
     }
 
     pub fn neighbor_function_primal(&self) -> impl Fn(VertID) -> Vec<VertID> + '_ {
@@ -539,6 +1389,101 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
         hutspot::graph::find_ccs(&self.vert_ids(), self.neighbor_function_primal()).len() == 1
     }
 
+    // Returns the (connected) components of the mesh, each as its sets of vertices, (half)edges, and faces.
+    #[must_use]
+    pub fn components(&self) -> Vec<(HashSet<VertID>, HashSet<EdgeID>, HashSet<FaceID>)> {
+        hutspot::graph::find_ccs(&self.vert_ids(), self.neighbor_function_primal())
+            .into_iter()
+            .map(|verts| {
+                let edges = verts.iter().flat_map(|&vert_id| self.outgoing_iter(vert_id)).collect();
+                let faces = verts.iter().flat_map(|&vert_id| self.star_iter(vert_id)).collect();
+                (verts.into_iter().collect(), edges, faces)
+            })
+            .collect()
+    }
+
+    // Returns the number of boundary loops of the mesh.
+    #[must_use]
+    pub fn nr_boundary_loops(&self) -> usize {
+        self.boundary_loops().len()
+    }
+
+    // Returns the Euler characteristic of the mesh (V - E + F).
+    // See https://en.wikipedia.org/wiki/Euler_characteristic
+    // Note that E is the number of (undirected) edges, i.e. half of `nr_edges`, as every edge consists of two half-edges (also on the boundary).
+    #[must_use]
+    pub fn euler_characteristic(&self) -> i64 {
+        let nr_verts = i64::try_from(self.nr_verts()).unwrap();
+        let nr_edges = i64::try_from(self.nr_edges() / 2).unwrap();
+        let nr_faces = i64::try_from(self.nr_faces()).unwrap();
+        nr_verts - nr_edges + nr_faces
+    }
+
+    // Returns the genus of the mesh (the number of handles), from X = 2C - 2g - B.
+    // With X the Euler characteristic, C the number of components, and B the number of boundary loops.
+    // For a mesh with multiple components, this is the sum of the genera of its components.
+    #[must_use]
+    pub fn genus(&self) -> usize {
+        let nr_components = i64::try_from(self.components().len()).unwrap();
+        let nr_boundary_loops = i64::try_from(self.nr_boundary_loops()).unwrap();
+        usize::try_from((2 * nr_components - nr_boundary_loops - self.euler_characteristic()) / 2).unwrap()
+    }
+
+    // Returns the Betti numbers [b0, b1, b2] of the mesh.
+    // See https://en.wikipedia.org/wiki/Betti_number
+    //      b0: the number of components
+    //      b1: the number of independent loops (2g per closed component, 2g + B - 1 per component with B > 0 boundary loops)
+    //      b2: the number of closed components (components without boundary)
+    // Such that X = b0 - b1 + b2.
+    #[must_use]
+    pub fn betti_numbers(&self) -> [usize; 3] {
+        let components = self.components();
+        let b0 = components.len();
+        let b2 = components
+            .iter()
+            .filter(|(_, edges, _)| !edges.iter().any(|&edge_id| self.is_boundary(edge_id)))
+            .count();
+        let b1 = usize::try_from(i64::try_from(b0 + b2).unwrap() - self.euler_characteristic()).unwrap();
+        [b0, b1, b2]
+    }
+
+    // Construct the dual of the mesh: a vertex for every face, and a face for every vertex (with the faces around the vertex as its corners).
+    // Vertices on the boundary have no dual face, and faces without any interior corner have no dual vertex.
+    // Returns the dual mesh, and the mappings from the faces of this mesh to the dual vertices, and from the vertices of this mesh to the dual faces.
+    pub fn dual(&self) -> Result<(Self, BiHashMap<FaceID, VertID>, BiHashMap<VertID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        let face_to_index = self
+            .faces
+            .keys()
+            .enumerate()
+            .map(|(index, face_id)| (face_id, index))
+            .collect::<HashMap<_, _>>();
+        let interior_verts = self.verts.keys().filter(|&vert_id| !self.is_boundary_vertex(vert_id)).collect_vec();
+
+        // The faces around a vertex are in clockwise order, so reverse them to get an anticlockwise dual face.
+        let dual_faces = interior_verts
+            .iter()
+            .map(|&vert_id| self.star(vert_id).into_iter().rev().map(|face_id| face_to_index[&face_id]).collect_vec())
+            .collect_vec();
+
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            ..ConstructionOptions::default()
+        };
+        let (dual, dual_vert_map, dual_face_map, _) = Self::from_faces_with(&dual_faces, options)?;
+
+        let face_to_dual = face_to_index
+            .into_iter()
+            .filter_map(|(face_id, index)| dual_vert_map.get_by_left(&index).map(|&dual_vert_id| (face_id, dual_vert_id)))
+            .collect();
+        let vert_to_dual = interior_verts
+            .into_iter()
+            .enumerate()
+            .map(|(index, vert_id)| (vert_id, dual_face_map.get_by_left(&index).copied().unwrap()))
+            .collect();
+
+        Ok((dual, face_to_dual, vert_to_dual))
+    }
+
     #[must_use]
     pub fn wedges(&self, a: VertID, b: VertID, c: VertID) -> (Vec<VertID>, Vec<VertID>) {
         // First wedge is a to c (around b)
@@ -559,192 +1504,969 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
 }
 
 impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default + Clone> Douconel<VertID, V, EdgeID, E, FaceID, F> {
-    pub fn split_edge(&mut self, edge_id: EdgeID) -> (VertID, [FaceID; 4]) {
+    // Splits the given edge (between two triangles) into two edges, by inserting a new vertex that is connected to the two opposite vertices.
+    // Returns the new vertex and the four faces (the first two re-use the original ids).
+    // The new elements get default data (use `split_edge_with` and `DefaultPolicy` to copy the data of the faces they are split from).
+    // Returns an error if the edge is on the boundary (see `split_edge_at` for boundary edges), or if it is not adjacent to two triangles.
+    pub fn split_edge(&mut self, edge_id: EdgeID) -> Result<(VertID, [FaceID; 4]), MeshError<VertID, EdgeID, FaceID>> {
+        self.split_edge_with(edge_id, &DefaultFacePolicy)
+    }
+
+    // Same as `split_edge`, with the data of the new elements given by `policy`.
+    pub fn split_edge_with(
+        &mut self,
+        edge_id: EdgeID,
+        policy: &impl AttributePolicy<V, E, F>,
+    ) -> Result<(VertID, [FaceID; 4]), MeshError<VertID, EdgeID, FaceID>> {
+        let (v_a, v_b) = self.endpoints(edge_id);
+        if self.is_boundary_edge(edge_id) {
+            return Err(MeshError::BoundaryEdge(v_a, v_b));
+        }
+
         // First face
         let e_ab = edge_id;
         let e_b0 = self.next(e_ab);
         let e_0a = self.next(e_b0);
-        assert!(self.next(e_0a) == e_ab);
-
-        let v_a = self.root(e_ab);
-        let v_b = self.root(e_b0);
         let v_0 = self.root(e_0a);
 
         // Second face
         let e_ba = self.twin(edge_id);
         let e_a1 = self.next(e_ba);
         let e_1b = self.next(e_a1);
-        assert!(self.next(e_1b) == e_ba);
-
-        assert!(self.root(e_ba) == v_b);
-        assert!(self.root(e_a1) == v_a);
         let v_1 = self.root(e_1b);
 
+        if self.next(e_0a) != e_ab || self.next(e_1b) != e_ba {
+            return Err(MeshError::NotTriangular(v_a, v_b));
+        }
+
         // Four new faces (re-use original id for first 2)
         let f_0 = self.face(e_ab);
-        self.face_rep.insert(f_0, e_0a);
+        self.set_face_rep(f_0, e_0a);
 
         let f_1 = self.face(e_ba);
-        self.face_rep.insert(f_1, e_a1);
+        self.set_face_rep(f_1, e_a1);
 
-        let f_2 = self.faces.insert(F::default());
-        self.face_rep.insert(f_2, e_b0);
+        let f_2 = self.insert_face(policy.split_face_attr(&self.faces[f_0]));
+        self.set_face_rep(f_2, e_b0);
 
-        let f_3 = self.faces.insert(F::default());
-        self.face_rep.insert(f_3, e_1b);
+        let f_3 = self.insert_face(policy.split_face_attr(&self.faces[f_1]));
+        self.set_face_rep(f_3, e_1b);
 
         // Six new edges (with next six available ids)
 
         // f_0
         let e_ax = e_ab;
-        let e_x0 = self.edges.insert(E::default());
+        let e_x0 = self.insert_edge(policy.new_edge_attr());
 
         // f_1
         let e_xa = e_ba;
-        let e_1x = self.edges.insert(E::default());
+        let e_1x = self.insert_edge(policy.new_edge_attr());
 
         // f_2
-        let e_xb = self.edges.insert(E::default());
-        let e_0x = self.edges.insert(E::default());
+        let e_xb = self.insert_edge(policy.split_edge_attr(&self.edges[e_ab]));
+        let e_0x = self.insert_edge(policy.new_edge_attr());
 
         // f_3
-        let e_bx = self.edges.insert(E::default());
-        let e_x1 = self.edges.insert(E::default());
+        let e_bx = self.insert_edge(policy.split_edge_attr(&self.edges[e_ba]));
+        let e_x1 = self.insert_edge(policy.new_edge_attr());
 
-        // One new vertex (with next available id)
-        let v_x = self.verts.insert(V::default());
-        self.vert_rep.insert(v_x, e_xa);
+        // One new vertex (with next available id), halfway between a and b
+        let v_x = self.insert_vertex(policy.interpolate_vertex(&self.verts[v_a], &self.verts[v_b], 0.5));
+        self.set_vert_rep(v_x, e_xa);
 
-        self.vert_rep.insert(v_b, e_b0);
-        self.vert_rep.insert(v_a, e_a1);
+        self.set_vert_rep(v_b, e_b0);
+        self.set_vert_rep(v_a, e_a1);
 
         // Set the edges correctly
-        self.edge_root.insert(e_ax, v_a);
-        self.edge_face.insert(e_ax, f_0);
-        self.edge_next.insert(e_ax, e_x0);
-        self.edge_twin.insert(e_ax, e_xa);
+        self.set_root(e_ax, v_a);
+        self.set_face(e_ax, Some(f_0));
+        self.set_next(e_ax, e_x0);
+        self.set_twin(e_ax, e_xa);
+
+        self.set_root(e_xa, v_x);
+        self.set_face(e_xa, Some(f_1));
+        self.set_next(e_xa, e_a1);
+        self.set_twin(e_xa, e_ax);
+
+        self.set_root(e_bx, v_b);
+        self.set_face(e_bx, Some(f_3));
+        self.set_next(e_bx, e_x1);
+        self.set_twin(e_bx, e_xb);
+
+        self.set_root(e_xb, v_x);
+        self.set_face(e_xb, Some(f_2));
+        self.set_next(e_xb, e_b0);
+        self.set_twin(e_xb, e_bx);
+
+        self.set_root(e_0x, v_0);
+        self.set_face(e_0x, Some(f_2));
+        self.set_next(e_0x, e_xb);
+        self.set_twin(e_0x, e_x0);
+
+        self.set_root(e_x0, v_x);
+        self.set_face(e_x0, Some(f_0));
+        self.set_next(e_x0, e_0a);
+        self.set_twin(e_x0, e_0x);
+
+        self.set_root(e_1x, v_1);
+        self.set_face(e_1x, Some(f_1));
+        self.set_next(e_1x, e_xa);
+        self.set_twin(e_1x, e_x1);
+
+        self.set_root(e_x1, v_x);
+        self.set_face(e_x1, Some(f_3));
+        self.set_next(e_x1, e_1b);
+        self.set_twin(e_x1, e_1x);
+
+        self.set_face(e_a1, Some(f_1));
+        self.set_next(e_a1, e_1x);
+
+        self.set_face(e_1b, Some(f_3));
+        self.set_next(e_1b, e_bx);
+
+        self.set_face(e_b0, Some(f_2));
+        self.set_next(e_b0, e_0x);
+
+        self.set_face(e_0a, Some(f_0));
+        self.set_next(e_0a, e_ax);
+
+        Ok((v_x, [f_0, f_1, f_2, f_3]))
+    }
 
-        self.edge_root.insert(e_xa, v_x);
-        self.edge_face.insert(e_xa, f_1);
-        self.edge_next.insert(e_xa, e_a1);
-        self.edge_twin.insert(e_xa, e_ax);
+    // Subdivides the given edge into two edges, by inserting a new vertex on it (without splitting the adjacent faces).
+    // The adjacent faces (if not on the boundary) get the new vertex as an additional corner.
+    // Returns the new vertex and the two new edges (from the new vertex to the end of the edge, and from the new vertex to the root of the edge).
+    pub fn subdivide_edge(&mut self, edge_id: EdgeID) -> (VertID, [EdgeID; 2]) {
+        self.subdivide_edge_with(edge_id, 0.5, &DefaultPolicy)
+    }
 
-        self.edge_root.insert(e_bx, v_b);
-        self.edge_face.insert(e_bx, f_3);
-        self.edge_next.insert(e_bx, e_x1);
-        self.edge_twin.insert(e_bx, e_xb);
+    // Same as `subdivide_edge`, with the new vertex at parameter `t` along the edge (from its root at t = 0, to its end at t = 1), and the data of the new elements given by `policy`.
+    pub fn subdivide_edge_with(&mut self, edge_id: EdgeID, t: f64, policy: &impl AttributePolicy<V, E, F>) -> (VertID, [EdgeID; 2]) {
+        //      a                 a
+        //      *                 *
+        //     | ^               | ^
+        // e_ab| |e_ba   =>  e_ax| |e_xa
+        //     v |               v |
+        //      *                 * x
+        //      b                | ^
+        //                   e_xb| |e_bx
+        //                       v |
+        //                        *
+        //                        b
+        let e_ab = edge_id;
+        let e_ba = self.twin(edge_id);
+        let (v_a, v_b) = self.endpoints(e_ab);
+        let e_after_ab = self.next(e_ab);
+        let e_after_ba = self.next(e_ba);
 
-        self.edge_root.insert(e_xb, v_x);
-        self.edge_face.insert(e_xb, f_2);
-        self.edge_next.insert(e_xb, e_b0);
-        self.edge_twin.insert(e_xb, e_bx);
+        // One new vertex (with next available id)
+        let v_x = self.insert_vertex(policy.interpolate_vertex(&self.verts[v_a], &self.verts[v_b], t));
 
-        self.edge_root.insert(e_0x, v_0);
-        self.edge_face.insert(e_0x, f_2);
-        self.edge_next.insert(e_0x, e_xb);
-        self.edge_twin.insert(e_0x, e_x0);
+        // The original edges become a -> x and b -> x
+        let e_ax = e_ab;
+        let e_bx = e_ba;
 
-        self.edge_root.insert(e_x0, v_x);
-        self.edge_face.insert(e_x0, f_0);
-        self.edge_next.insert(e_x0, e_0a);
-        self.edge_twin.insert(e_x0, e_0x);
+        // Two new edges: x -> b and x -> a
+        let e_xb = self.insert_edge(policy.split_edge_attr(&self.edges[e_ab]));
+        let e_xa = self.insert_edge(policy.split_edge_attr(&self.edges[e_ba]));
 
-        self.edge_root.insert(e_1x, v_1);
-        self.edge_face.insert(e_1x, f_1);
-        self.edge_next.insert(e_1x, e_xa);
-        self.edge_twin.insert(e_1x, e_x1);
+        self.set_vert_rep(v_x, e_xb);
 
-        self.edge_root.insert(e_x1, v_x);
-        self.edge_face.insert(e_x1, f_3);
-        self.edge_next.insert(e_x1, e_1b);
-        self.edge_twin.insert(e_x1, e_1x);
+        self.set_root(e_xb, v_x);
+        if let Some(&face_id) = self.edge_face.get(e_ax) {
+            self.set_face(e_xb, Some(face_id));
+        }
+        self.set_next(e_xb, e_after_ab);
+        self.set_twin(e_xb, e_bx);
 
-        self.edge_face.insert(e_a1, f_1);
-        self.edge_next.insert(e_a1, e_1x);
+        self.set_root(e_xa, v_x);
+        if let Some(&face_id) = self.edge_face.get(e_bx) {
+            self.set_face(e_xa, Some(face_id));
+        }
+        self.set_next(e_xa, e_after_ba);
+        self.set_twin(e_xa, e_ax);
 
-        self.edge_face.insert(e_1b, f_3);
-        self.edge_next.insert(e_1b, e_bx);
+        self.set_next(e_ax, e_xb);
+        self.set_twin(e_ax, e_xa);
 
-        self.edge_face.insert(e_b0, f_2);
-        self.edge_next.insert(e_b0, e_0x);
+        self.set_next(e_bx, e_xa);
+        self.set_twin(e_bx, e_xb);
 
-        self.edge_face.insert(e_0a, f_0);
-        self.edge_next.insert(e_0a, e_ax);
+        (v_x, [e_xb, e_xa])
+    }
 
-        (v_x, [f_0, f_1, f_2, f_3])
+    // Splits the given (polygonal) face into triangles, by inserting a new vertex that is connected to all corners of the face.
+    // A face with n edges is split into n triangles, the i-th triangle contains the i-th edge of the face.
+    // Returns the new vertex and the new faces (the first face re-uses the original id).
+    pub fn split_face(&mut self, face_id: FaceID) -> (VertID, Vec<FaceID>) {
+        self.split_face_with(face_id, &DefaultPolicy)
     }
 
-    pub fn split_face(&mut self, face_id: FaceID) -> (VertID, [FaceID; 3]) {
+    // Same as `split_face`, with the data of the new elements given by `policy`.
+    pub fn split_face_with(&mut self, face_id: FaceID, policy: &impl AttributePolicy<V, E, F>) -> (VertID, Vec<FaceID>) {
+        // Original face
         let edges = self.edges(face_id);
-        // let centroid = self.centroid(face_id);
+        let corners = self.corners(face_id);
+        let n = edges.len();
+
+        // New faces (re-use original id for the first one)
+        let new_faces = (0..n)
+            .map(|i| {
+                if i == 0 {
+                    face_id
+                } else {
+                    self.insert_face(policy.split_face_attr(&self.faces[face_id]))
+                }
+            })
+            .collect_vec();
 
-        // Original face
-        let e_01 = edges[0];
-        let v_0 = self.root(e_01);
+        // Two new edges per corner: from the corner to the new vertex, and from the new vertex to the corner
+        let edges_to_x = (0..n).map(|_| self.insert_edge(policy.new_edge_attr())).collect_vec();
+        let edges_from_x = (0..n).map(|_| self.insert_edge(policy.new_edge_attr())).collect_vec();
+
+        // One new vertex (with next available id), at the average of the corners (interpolated one corner at a time)
+        let mut vert = policy.interpolate_vertex(&self.verts[corners[0]], &self.verts[corners[1]], 0.5);
+        let mut weight = 2.;
+        for &corner in corners.iter().skip(2) {
+            weight += 1.;
+            vert = policy.interpolate_vertex(&vert, &self.verts[corner], 1. / weight);
+        }
+        let v_x = self.insert_vertex(vert);
+        self.set_vert_rep(v_x, edges_from_x[0]);
+
+        // The i-th triangle is (v_i, v_i+1, x)
+        for i in 0..n {
+            let f_i = new_faces[i];
+            let e_i = edges[i];
+            let e_to_x = edges_to_x[i];
+            let e_from_x = edges_from_x[i];
+
+            self.set_face_rep(f_i, e_i);
+
+            self.set_face(e_i, Some(f_i));
+            self.set_next(e_i, e_to_x);
+
+            self.set_root(e_to_x, corners[(i + 1) % n]);
+            self.set_face(e_to_x, Some(f_i));
+            self.set_next(e_to_x, e_from_x);
+            self.set_twin(e_to_x, edges_from_x[(i + 1) % n]);
+
+            self.set_root(e_from_x, v_x);
+            self.set_face(e_from_x, Some(f_i));
+            self.set_next(e_from_x, e_i);
+            self.set_twin(e_from_x, edges_to_x[(i + n - 1) % n]);
+        }
 
-        let e_12 = edges[1];
-        let v_1 = self.root(e_12);
+        (v_x, new_faces)
+    }
+
+    // Splits the given (polygonal) face into two faces, by inserting an edge (diagonal) between two of its non-adjacent corners.
+    // Returns the new edge (from `v_a` to `v_b`) and the two faces (the first face re-uses the original id, and contains the twin of the new edge).
+    // Returns an error if the vertices are not two non-adjacent corners of the face, or if they are already connected.
+    pub fn split_face_by_diagonal(&mut self, face_id: FaceID, v_a: VertID, v_b: VertID) -> Result<(EdgeID, [FaceID; 2]), MeshError<VertID, EdgeID, FaceID>> {
+        self.split_face_by_diagonal_with(face_id, v_a, v_b, &DefaultPolicy)
+    }
+
+    // Same as `split_face_by_diagonal`, with the data of the new elements given by `policy`.
+    pub fn split_face_by_diagonal_with(
+        &mut self,
+        face_id: FaceID,
+        v_a: VertID,
+        v_b: VertID,
+        policy: &impl AttributePolicy<V, E, F>,
+    ) -> Result<(EdgeID, [FaceID; 2]), MeshError<VertID, EdgeID, FaceID>> {
+        let edges = self.edges(face_id);
+        let e_a = edges.iter().copied().find(|&edge_id| self.root(edge_id) == v_a);
+        let e_b = edges.iter().copied().find(|&edge_id| self.root(edge_id) == v_b);
+        let (Some(e_a), Some(e_b)) = (e_a, e_b) else {
+            return Err(MeshError::NotDiagonal(v_a, v_b));
+        };
+        if v_a == v_b || self.toor(e_a) == v_b || self.toor(e_b) == v_a {
+            return Err(MeshError::NotDiagonal(v_a, v_b));
+        }
+        if self.edge_between_verts(v_a, v_b).is_some() {
+            return Err(MeshError::DuplicateEdge(v_a, v_b));
+        }
 
-        let e_20 = edges[2];
-        let v_2 = self.root(e_20);
+        // The edges that end in a and b
+        let e_to_a = self.prev(e_a);
+        let e_to_b = self.prev(e_b);
 
-        // Two new faces (original face stays the same)
+        // First face (re-use original id) goes from a to b, and back to a through the diagonal
         let f_0 = face_id;
+        // Second face goes from b to a, and back to b through the diagonal
+        let f_1 = self.insert_face(policy.split_face_attr(&self.faces[face_id]));
 
-        let f_1 = self.faces.insert(self.faces[face_id].clone());
-        let f_2 = self.faces.insert(self.faces[face_id].clone());
+        let e_ab = self.insert_edge(policy.new_edge_attr());
+        let e_ba = self.insert_edge(policy.new_edge_attr());
 
-        self.face_rep.insert(f_1, e_12);
-        self.face_rep.insert(f_2, e_20);
+        self.set_root(e_ab, v_a);
+        self.set_face(e_ab, Some(f_1));
+        self.set_next(e_ab, e_b);
+        self.set_twin(e_ab, e_ba);
 
-        // Six new edges (with next six available ids)
-        let e_x0 = self.edges.insert(E::default());
-        let e_x1 = self.edges.insert(E::default());
-        let e_x2 = self.edges.insert(E::default());
-        let e_0x = self.edges.insert(E::default());
-        let e_1x = self.edges.insert(E::default());
-        let e_2x = self.edges.insert(E::default());
-
-        let v_x = self.verts.insert(V::default());
-        self.vert_rep.insert(v_x, e_x0);
-
-        self.edge_root.insert(e_x0, v_x);
-        self.edge_face.insert(e_x0, f_0);
-        self.edge_next.insert(e_x0, e_01);
-        self.edge_twin.insert(e_x0, e_0x);
-
-        self.edge_root.insert(e_x1, v_x);
-        self.edge_face.insert(e_x1, f_1);
-        self.edge_next.insert(e_x1, e_12);
-        self.edge_twin.insert(e_x1, e_1x);
-
-        self.edge_root.insert(e_x2, v_x);
-        self.edge_face.insert(e_x2, f_2);
-        self.edge_next.insert(e_x2, e_20);
-        self.edge_twin.insert(e_x2, e_2x);
-
-        self.edge_root.insert(e_0x, v_0);
-        self.edge_face.insert(e_0x, f_2);
-        self.edge_next.insert(e_0x, e_x2);
-        self.edge_twin.insert(e_0x, e_x0);
-
-        self.edge_root.insert(e_1x, v_1);
-        self.edge_face.insert(e_1x, f_0);
-        self.edge_next.insert(e_1x, e_x0);
-        self.edge_twin.insert(e_1x, e_x1);
-
-        self.edge_root.insert(e_2x, v_2);
-        self.edge_face.insert(e_2x, f_1);
-        self.edge_next.insert(e_2x, e_x1);
-        self.edge_twin.insert(e_2x, e_x2);
-
-        self.edge_face.insert(e_01, f_0);
-        self.edge_face.insert(e_12, f_1);
-        self.edge_face.insert(e_20, f_2);
-        self.edge_next.insert(e_01, e_1x);
-        self.edge_next.insert(e_12, e_2x);
-        self.edge_next.insert(e_20, e_0x);
-
-        (v_x, [f_0, f_1, f_2])
+        self.set_root(e_ba, v_b);
+        self.set_face(e_ba, Some(f_0));
+        self.set_next(e_ba, e_a);
+        self.set_twin(e_ba, e_ab);
+
+        self.set_next(e_to_b, e_ba);
+        self.set_next(e_to_a, e_ab);
+
+        self.set_face_rep(f_0, e_a);
+        self.set_face_rep(f_1, e_b);
+        for edge_id in self.edges(f_1) {
+            self.set_face(edge_id, Some(f_1));
+        }
+
+        Ok((e_ab, [f_0, f_1]))
+    }
+
+    // Flips the edge between two triangles, such that it connects the two opposite vertices.
+    //                a                        a
+    //                *                        *
+    //              / | \                    / \
+    //             /  |  \                  /   \
+    //          c *   |   * d     =>     c * <-- * d
+    //             \  |  /                  \   /
+    //              \ v /                    \ /
+    //                *                        *
+    //                b                        b
+    // Returns an error if the edge is on the boundary, if it is not adjacent to two triangles, or if the opposite vertices are already connected.
+    pub fn flip_edge(&mut self, edge_id: EdgeID) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        let (v_a, v_b) = self.endpoints(edge_id);
+        if self.is_boundary_edge(edge_id) {
+            return Err(MeshError::BoundaryEdge(v_a, v_b));
+        }
+
+        // First face
+        let e_ab = edge_id;
+        let e_bc = self.next(e_ab);
+        let e_ca = self.next(e_bc);
+
+        // Second face
+        let e_ba = self.twin(edge_id);
+        let e_ad = self.next(e_ba);
+        let e_db = self.next(e_ad);
+
+        if self.next(e_ca) != e_ab || self.next(e_db) != e_ba {
+            return Err(MeshError::NotTriangular(v_a, v_b));
+        }
+
+        let v_c = self.root(e_ca);
+        let v_d = self.root(e_db);
+        if v_c == v_d || self.edge_between_verts(v_c, v_d).is_some() {
+            return Err(MeshError::AlreadyConnected(v_c, v_d));
+        }
+
+        let f_0 = self.face(e_ab);
+        let f_1 = self.face(e_ba);
+
+        // The flipped edge (re-use original ids)
+        let e_dc = e_ab;
+        let e_cd = e_ba;
+
+        // First face becomes (c, a, d)
+        self.set_root(e_dc, v_d);
+        self.set_next(e_dc, e_ca);
+        self.set_next(e_ca, e_ad);
+        self.set_next(e_ad, e_dc);
+        self.set_face(e_ad, Some(f_0));
+        self.set_face_rep(f_0, e_dc);
+
+        // Second face becomes (d, b, c)
+        self.set_root(e_cd, v_c);
+        self.set_next(e_cd, e_db);
+        self.set_next(e_db, e_bc);
+        self.set_next(e_bc, e_cd);
+        self.set_face(e_bc, Some(f_1));
+        self.set_face_rep(f_1, e_cd);
+
+        // The original endpoints might have had the flipped edge as representative
+        self.set_vert_rep(v_a, e_ad);
+        self.set_vert_rep(v_b, e_bc);
+
+        Ok(())
+    }
+
+    // Same as `flip_edge`, but the flipped edge (a new diagonal) gets its data from `policy`, instead of keeping the data of the original edge.
+    pub fn flip_edge_with(&mut self, edge_id: EdgeID, policy: &impl AttributePolicy<V, E, F>) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        self.flip_edge(edge_id)?;
+        let twin_id = self.twin(edge_id);
+        self.edges_mut()[edge_id] = policy.new_edge_attr();
+        self.edges_mut()[twin_id] = policy.new_edge_attr();
+        Ok(())
+    }
+
+    // Collapses the edge between two triangles, such that its two endpoints are merged into one vertex.
+    //                a                        a
+    //                *                        *
+    //              / | \                    / \
+    //             /  |  \                  /   \
+    //          c *   |   * d     =>     c *     * d
+    //             \  |  /
+    //              \ v /
+    //                *
+    //                b
+    // The root (a) survives, and keeps its data (use `collapse_edge_with` to merge the data of a and b). The toor (b) is removed.
+    // The two adjacent triangles are removed, and their remaining edges are merged (c-b with c-a, and d-b with d-a).
+    // Returns the surviving vertex and the removed elements.
+    // Returns an error if the edge is on the boundary, if it is not adjacent to two triangles, or if the collapse violates the link condition.
+    pub fn collapse_edge(&mut self, edge_id: EdgeID) -> Result<(VertID, Removed<VertID, EdgeID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        self.collapse_edge_inner(edge_id).map(|(v_a, removed, _)| (v_a, removed))
+    }
+
+    // Same as `collapse_edge`, with the data of the surviving vertex given by `policy` (halfway between the root and the toor).
+    pub fn collapse_edge_with(
+        &mut self,
+        edge_id: EdgeID,
+        policy: &impl AttributePolicy<V, E, F>,
+    ) -> Result<(VertID, Removed<VertID, EdgeID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        let (v_a, removed, vert_b) = self.collapse_edge_inner(edge_id)?;
+        self.verts_mut()[v_a] = policy.interpolate_vertex(&self.verts[v_a], &vert_b, 0.5);
+        Ok((v_a, removed))
+    }
+
+    // Collapses the edge (see `collapse_edge`), and also returns the data of the removed vertex.
+    fn collapse_edge_inner(&mut self, edge_id: EdgeID) -> Result<(VertID, Removed<VertID, EdgeID, FaceID>, V), MeshError<VertID, EdgeID, FaceID>> {
+        let (v_a, v_b) = self.endpoints(edge_id);
+        if self.is_boundary_edge(edge_id) {
+            return Err(MeshError::BoundaryEdge(v_a, v_b));
+        }
+
+        // First face
+        let e_ab = edge_id;
+        let e_bc = self.next(e_ab);
+        let e_ca = self.next(e_bc);
+
+        // Second face
+        let e_ba = self.twin(edge_id);
+        let e_ad = self.next(e_ba);
+        let e_db = self.next(e_ad);
+
+        if self.next(e_ca) != e_ab || self.next(e_db) != e_ba {
+            return Err(MeshError::NotTriangular(v_a, v_b));
+        }
+
+        let v_c = self.root(e_ca);
+        let v_d = self.root(e_db);
+
+        // Link condition: the common neighbors of a and b are exactly c and d.
+        let neighbors_a = self.vneighbors(v_a).into_iter().collect::<HashSet<_>>();
+        let neighbors_b = self.vneighbors(v_b).into_iter().collect::<HashSet<_>>();
+        if v_c == v_d || neighbors_a.intersection(&neighbors_b).copied().collect::<HashSet<_>>() != HashSet::from([v_c, v_d]) {
+            return Err(MeshError::LinkCondition(v_a, v_b));
+        }
+
+        // An interior edge between two boundary vertices would pinch the mesh.
+        if self.is_boundary_vertex(v_a) && self.is_boundary_vertex(v_b) {
+            return Err(MeshError::LinkCondition(v_a, v_b));
+        }
+
+        // The opposite vertices must keep at least two neighbors (boundary) or three neighbors (interior).
+        for v_x in [v_c, v_d] {
+            let min_valence = if self.is_boundary_vertex(v_x) { 3 } else { 4 };
+            if self.vneighbors(v_x).len() < min_valence {
+                return Err(MeshError::LinkCondition(v_a, v_b));
+            }
+        }
+
+        let f_0 = self.face(e_ab);
+        let f_1 = self.face(e_ba);
+
+        // The edges that survive (twins of the edges of the two triangles)
+        let e_cb = self.twin(e_bc);
+        let e_ac = self.twin(e_ca);
+        let e_da = self.twin(e_ad);
+        let e_bd = self.twin(e_db);
+
+        let outgoing_b = self.outgoing(v_b);
+
+        // Remove the two triangles and their edges
+        let removed_edges = vec![e_ab, e_bc, e_ca, e_ba, e_ad, e_db];
+        for &removed_edge_id in &removed_edges {
+            self.remove_edge(removed_edge_id);
+        }
+        self.remove_face(f_0);
+        self.remove_face(f_1);
+
+        // Merge c-b with c-a, and d-b with d-a
+        self.set_twin(e_cb, e_ac);
+        self.set_twin(e_ac, e_cb);
+        self.set_twin(e_bd, e_da);
+        self.set_twin(e_da, e_bd);
+
+        // All remaining edges of b now start at a
+        for outgoing_edge_id in outgoing_b {
+            if self.edges.contains_key(outgoing_edge_id) {
+                self.set_root(outgoing_edge_id, v_a);
+            }
+        }
+
+        // The vertices might have had a removed edge as representative
+        self.set_vert_rep(v_a, e_ac);
+        self.set_vert_rep(v_c, e_cb);
+        self.set_vert_rep(v_d, e_da);
+
+        let vert_b = self.remove_vertex(v_b).unwrap();
+
+        Ok((
+            v_a,
+            Removed {
+                verts: vec![v_b],
+                edges: removed_edges,
+                faces: vec![f_0, f_1],
+            },
+            vert_b,
+        ))
+    }
+
+    // Deletes the given face from the mesh. See `delete_faces`.
+    pub fn delete_face(&mut self, face_id: FaceID) -> Result<Removed<VertID, EdgeID, FaceID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.delete_faces(&HashSet::from([face_id]))
+    }
+
+    // Deletes the given vertex and its star (all faces around it) from the mesh. See `delete_faces`.
+    pub fn delete_vertex(&mut self, vert_id: VertID) -> Result<Removed<VertID, EdgeID, FaceID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.check_vert(vert_id)?;
+        self.delete_faces(&self.star(vert_id).into_iter().collect())
+    }
+
+    // Deletes the given faces from the mesh.
+    // The edges of the deleted faces become boundary edges. Edges (and vertices) that are no longer part of any face are removed.
+    // Returns the removed elements.
+    // Returns an error (without modifying the mesh) if a face is not in the mesh, or if a vertex would end up with more than one boundary (not a 2-manifold).
+    pub fn delete_faces(&mut self, face_ids: &HashSet<FaceID>) -> Result<Removed<VertID, EdgeID, FaceID>, MeshError<VertID, EdgeID, FaceID>> {
+        for &face_id in face_ids {
+            self.check_face(face_id)?;
+        }
+        let affected_verts = face_ids.iter().flat_map(|&face_id| self.corners(face_id)).unique().collect_vec();
+
+        // For every affected vertex, the remaining faces around it must form a single fan.
+        // Keep an outgoing edge (with a remaining face) for each affected vertex that survives.
+        let mut surviving_verts = vec![];
+        let mut isolated_verts = vec![];
+        for &vert_id in &affected_verts {
+            let outgoing = self.outgoing(vert_id);
+            let alive = outgoing
+                .iter()
+                .map(|&edge_id| self.edge_face.get(edge_id).is_some_and(|face_id| !face_ids.contains(face_id)))
+                .collect_vec();
+            let fans = (0..alive.len()).filter(|&i| alive[i] && !alive[(i + alive.len() - 1) % alive.len()]).count();
+            match fans {
+                0 => isolated_verts.push(vert_id),
+                1 => surviving_verts.push((vert_id, outgoing[alive.iter().position(|&a| a).unwrap()])),
+                _ => return Err(MeshError::NonManifoldVertex(vert_id)),
+            }
+        }
+
+        let mut removed = Removed::default();
+
+        // Remove the faces, their edges become boundary edges.
+        let mut boundary_edges = vec![];
+        for &face_id in face_ids {
+            for edge_id in self.edges(face_id) {
+                self.set_face(edge_id, None);
+                boundary_edges.push(edge_id);
+            }
+            self.remove_face(face_id);
+            removed.faces.push(face_id);
+        }
+
+        // Remove the edges that are on the boundary on both sides.
+        for edge_id in boundary_edges {
+            if self.edges.contains_key(edge_id) && self.is_boundary(self.twin(edge_id)) {
+                let twin_id = self.twin(edge_id);
+                self.remove_edge(edge_id);
+                self.remove_edge(twin_id);
+                removed.edges.extend([edge_id, twin_id]);
+            }
+        }
+
+        // Remove the vertices that are no longer part of any face.
+        for vert_id in isolated_verts {
+            self.remove_vertex(vert_id);
+            removed.verts.push(vert_id);
+        }
+
+        // Link the boundary edges around the surviving vertices.
+        for (vert_id, edge_id) in surviving_verts {
+            self.link_boundary(vert_id, edge_id);
+        }
+
+        Ok(removed)
+    }
+
+    // Links the incoming boundary edge of the given vertex to its outgoing boundary edge (if the vertex is on the boundary).
+    // Only requires the faces around the vertex to be valid, starting from the given outgoing edge (that must have a face).
+    fn link_boundary(&mut self, vert_id: VertID, edge_id: EdgeID) {
+        // Rotate (through the faces) until the outgoing boundary edge is found.
+        let mut outgoing_boundary = edge_id;
+        loop {
+            outgoing_boundary = self.twin(self.prev(outgoing_boundary));
+            if self.is_boundary(outgoing_boundary) {
+                break;
+            }
+            if outgoing_boundary == edge_id {
+                // Not on the boundary.
+                return;
+            }
+        }
+
+        // Rotate (through the faces) in the other direction until the incoming boundary edge is found.
+        let mut incoming_boundary = self.twin(edge_id);
+        while !self.is_boundary(incoming_boundary) {
+            incoming_boundary = self.twin(self.next(incoming_boundary));
+        }
+
+        self.set_next(incoming_boundary, outgoing_boundary);
+        self.set_vert_rep(vert_id, outgoing_boundary);
+    }
+
+    // Rebuilds the vertices, edges, and faces into dense storage (removing any gaps left by deleted elements).
+    // Returns the mappings from old IDs to new IDs.
+    pub fn compact(&mut self) -> (BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>) {
+        let mut vert_map = BiHashMap::new();
+        let mut verts = SlotMap::with_capacity_and_key(self.verts.len());
+        for (old_id, vert) in std::mem::take(self.verts_mut()) {
+            vert_map.insert(old_id, verts.insert(vert));
+        }
+        self.verts = verts;
+
+        let mut edge_map = BiHashMap::new();
+        let mut edges = SlotMap::with_capacity_and_key(self.edges.len());
+        for (old_id, edge) in std::mem::take(self.edges_mut()) {
+            edge_map.insert(old_id, edges.insert(edge));
+        }
+        self.edges = edges;
+
+        let mut face_map = BiHashMap::new();
+        let mut faces = SlotMap::with_capacity_and_key(self.faces.len());
+        for (old_id, face) in std::mem::take(self.faces_mut()) {
+            face_map.insert(old_id, faces.insert(face));
+        }
+        self.faces = faces;
+
+        let vert = |id: &VertID| vert_map.get_by_left(id).copied().unwrap();
+        let edge = |id: &EdgeID| edge_map.get_by_left(id).copied().unwrap();
+        let face = |id: &FaceID| face_map.get_by_left(id).copied().unwrap();
+
+        let edge_root = self.edge_root.iter().map(|(id, root)| (edge(&id), vert(root))).collect();
+        let edge_face = self.edge_face.iter().map(|(id, face_id)| (edge(&id), face(face_id))).collect();
+        let edge_next = self.edge_next.iter().map(|(id, next)| (edge(&id), edge(next))).collect();
+        let edge_twin = self.edge_twin.iter().map(|(id, twin)| (edge(&id), edge(twin))).collect();
+        let vert_rep = self.vert_rep.iter().map(|(id, rep)| (vert(&id), edge(rep))).collect();
+        let face_rep = self.face_rep.iter().map(|(id, rep)| (face(&id), edge(rep))).collect();
+        record_all(&mut self.edge_root, self.history.changes().map(|changes| &mut changes.edge_root), edge_root);
+        record_all(&mut self.edge_face, self.history.changes().map(|changes| &mut changes.edge_face), edge_face);
+        record_all(&mut self.edge_next, self.history.changes().map(|changes| &mut changes.edge_next), edge_next);
+        record_all(&mut self.edge_twin, self.history.changes().map(|changes| &mut changes.edge_twin), edge_twin);
+        record_all(&mut self.vert_rep, self.history.changes().map(|changes| &mut changes.vert_rep), vert_rep);
+        record_all(&mut self.face_rep, self.history.changes().map(|changes| &mut changes.face_rep), face_rep);
+
+        self.vert_props_mut().remap(&vert_map);
+        self.edge_props_mut().remap(&edge_map);
+        self.face_props_mut().remap(&face_map);
+
+        (vert_map, edge_map, face_map)
+    }
+
+    // Merges the two faces adjacent to the given edge into one face, by removing the edge (inverse of `split_face_by_diagonal`).
+    // The face of the edge survives, and keeps its data (use `merge_faces_with` to merge the data of both faces).
+    // Returns the surviving face and the removed elements.
+    // Returns an error if the edge is on the boundary, or if removing it would create a dangling vertex or a face that is bounded twice by the same edge.
+    pub fn merge_faces(&mut self, edge_id: EdgeID) -> Result<(FaceID, Removed<VertID, EdgeID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        self.merge_faces_inner(edge_id).map(|(f_0, removed, _)| (f_0, removed))
+    }
+
+    // Same as `merge_faces`, with the data of the surviving face given by `policy`.
+    pub fn merge_faces_with(
+        &mut self,
+        edge_id: EdgeID,
+        policy: &impl AttributePolicy<V, E, F>,
+    ) -> Result<(FaceID, Removed<VertID, EdgeID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        let (f_0, removed, face_1) = self.merge_faces_inner(edge_id)?;
+        self.faces_mut()[f_0] = policy.merge_face_attr(&self.faces[f_0], &face_1);
+        Ok((f_0, removed))
+    }
+
+    // Merges the faces (see `merge_faces`), and also returns the data of the removed face.
+    fn merge_faces_inner(&mut self, edge_id: EdgeID) -> Result<(FaceID, Removed<VertID, EdgeID, FaceID>, F), MeshError<VertID, EdgeID, FaceID>> {
+        let (v_a, v_b) = self.endpoints(edge_id);
+        if self.is_boundary_edge(edge_id) {
+            return Err(MeshError::BoundaryEdge(v_a, v_b));
+        }
+
+        let e_ab = edge_id;
+        let e_ba = self.twin(edge_id);
+        let f_0 = self.face(e_ab);
+        let f_1 = self.face(e_ba);
+
+        // The two faces may not share any other edge, and both endpoints must keep at least two edges.
+        let shared_edges = self.edges(f_0).into_iter().filter(|&e| self.edge_face.get(self.twin(e)) == Some(&f_1)).count();
+        if f_0 == f_1 || shared_edges != 1 || self.outgoing(v_a).len() < 3 || self.outgoing(v_b).len() < 3 {
+            return Err(MeshError::NotMergeable(v_a, v_b));
+        }
+
+        let e_to_a = self.prev(e_ab);
+        let e_from_b = self.next(e_ab);
+        let e_to_b = self.prev(e_ba);
+        let e_from_a = self.next(e_ba);
+
+        // All edges of the second face now belong to the first face
+        for e in self.edges(f_1) {
+            self.set_face(e, Some(f_0));
+        }
+
+        // Skip the removed edges
+        self.set_next(e_to_a, e_from_a);
+        self.set_next(e_to_b, e_from_b);
+
+        self.set_face_rep(f_0, e_from_b);
+        self.set_vert_rep(v_a, e_from_a);
+        self.set_vert_rep(v_b, e_from_b);
+
+        self.remove_edge(e_ab);
+        self.remove_edge(e_ba);
+        let face_1 = self.remove_face(f_1).unwrap();
+
+        Ok((
+            f_0,
+            Removed {
+                verts: vec![],
+                edges: vec![e_ab, e_ba],
+                faces: vec![f_1],
+            },
+            face_1,
+        ))
+    }
+
+    // Adds all elements of another mesh to this mesh (as a separate component), with the values of their property layers.
+    // Returns the mappings from the vertices, edges, and faces of the other mesh to those in this mesh.
+    pub fn append(&mut self, other: Self) -> (BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>) {
+        let mut vert_map = BiHashMap::new();
+        for (other_id, vert) in other.verts {
+            let vert_id = self.add_vertex();
+            self.verts_mut()[vert_id] = vert;
+            vert_map.insert(other_id, vert_id);
+        }
+        let mut edge_map = BiHashMap::new();
+        for (other_id, edge) in other.edges {
+            let edge_id = self.add_edge();
+            self.edges_mut()[edge_id] = edge;
+            edge_map.insert(other_id, edge_id);
+        }
+        let mut face_map = BiHashMap::new();
+        for (other_id, face) in other.faces {
+            let face_id = self.add_face();
+            self.faces_mut()[face_id] = face;
+            face_map.insert(other_id, face_id);
+        }
+
+        let vert = |id: &VertID| vert_map.get_by_left(id).copied().unwrap();
+        let edge = |id: &EdgeID| edge_map.get_by_left(id).copied().unwrap();
+        let face = |id: &FaceID| face_map.get_by_left(id).copied().unwrap();
+        for (id, root) in &other.edge_root {
+            self.set_root(edge(&id), vert(root));
+        }
+        for (id, face_id) in &other.edge_face {
+            self.set_face(edge(&id), Some(face(face_id)));
+        }
+        for (id, next) in &other.edge_next {
+            self.set_next(edge(&id), edge(next));
+        }
+        for (id, twin) in &other.edge_twin {
+            self.set_twin(edge(&id), edge(twin));
+        }
+        for (id, rep) in &other.vert_rep {
+            self.set_vert_rep(vert(&id), edge(rep));
+        }
+        for (id, rep) in &other.face_rep {
+            self.set_face_rep(face(&id), edge(rep));
+        }
+
+        let keys = self.verts.keys().collect_vec();
+        self.vert_props_mut().append(&other.vert_props, &keys, &vert_map);
+        let keys = self.edges.keys().collect_vec();
+        self.edge_props_mut().append(&other.edge_props, &keys, &edge_map);
+        let keys = self.faces.keys().collect_vec();
+        self.face_props_mut().append(&other.face_props, &keys, &face_map);
+
+        (vert_map, edge_map, face_map)
+    }
+
+    // Glues another mesh to this mesh, along a boundary loop of each mesh (given by one of their boundary edges).
+    // The boundary edge `boundary_a` (u0 -> u1) of this mesh is paired with the boundary edge `boundary_b` (w0 -> w1) of the other mesh, such that u0 = w1 and u1 = w0.
+    // Going forward along the first loop, and backward along the second loop, all other boundary edges are paired in the same way.
+    // The paired boundary edges are removed, and the vertices of the other mesh are merged into those of this mesh.
+    // Returns the mappings from the vertices, edges, and faces of the other mesh to those in this mesh.
+    // Merged vertices are mapped to the vertex they are merged into, and removed boundary edges to the (former boundary) edge that replaced them as twin.
+    // Returns an error if the given edges do not exist (`MeshError::InvalidEdge`) or are not on the boundary (`MeshError::NotBoundaryEdge`),
+    // or if the boundary loops do not have the same number of edges (`MeshError::BoundaryLengthMismatch`).
+    // Returns an error (without modifying the mesh) if the result would not be a 2-manifold: if a vertex appears twice on one of the loops (`MeshError::NonManifoldVertex`,
+    // with the vertex of the mesh it belongs to), or if an edge of the other mesh would duplicate an edge of this mesh (`MeshError::DuplicateEdge`).
+    pub fn glue(
+        &mut self,
+        other: Self,
+        boundary_a: EdgeID,
+        boundary_b: EdgeID,
+    ) -> Result<(BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        if !self.edges.contains_key(boundary_a) {
+            return Err(MeshError::InvalidEdge(boundary_a));
+        }
+        if !self.is_boundary(boundary_a) {
+            return Err(MeshError::NotBoundaryEdge(boundary_a));
+        }
+        if !other.edges.contains_key(boundary_b) {
+            return Err(MeshError::InvalidEdge(boundary_b));
+        }
+        if !other.is_boundary(boundary_b) {
+            return Err(MeshError::NotBoundaryEdge(boundary_b));
+        }
+        let loop_a = [vec![boundary_a], self.nexts(boundary_a)].concat();
+        let nr_b = other.nexts(boundary_b).len() + 1;
+        if loop_a.len() != nr_b {
+            return Err(MeshError::BoundaryLengthMismatch(loop_a.len(), nr_b));
+        }
+        let n = loop_a.len();
+
+        // The vertices of the other loop (w_-i) that are merged into the vertices of this loop (u_i+1).
+        let other_loop_b = [vec![boundary_b], other.nexts(boundary_b)].concat();
+        let merged = (0..n)
+            .map(|i| (other.root(other_loop_b[(n - i) % n]), self.toor(loop_a[i])))
+            .collect::<HashMap<_, _>>();
+        if let Some(vert_id) = loop_a.iter().map(|&edge_id| self.root(edge_id)).duplicates().next() {
+            return Err(MeshError::NonManifoldVertex(vert_id));
+        }
+        if let Some(vert_id) = other_loop_b.iter().map(|&edge_id| other.root(edge_id)).duplicates().next() {
+            return Err(MeshError::NonManifoldVertex(vert_id));
+        }
+        // Apart from the paired boundary edges, no edge of the other mesh may connect two merged vertices that are already connected.
+        for edge_id in other.edges.keys() {
+            if other_loop_b.contains(&edge_id) || other_loop_b.contains(&other.twin(edge_id)) {
+                continue;
+            }
+            let (w_a, w_b) = other.endpoints(edge_id);
+            if let (Some(&u_a), Some(&u_b)) = (merged.get(&w_a), merged.get(&w_b))
+                && self.edge_between_verts(u_a, u_b).is_some()
+            {
+                return Err(MeshError::DuplicateEdge(u_a, u_b));
+            }
+        }
+
+        let (mut vert_map, mut edge_map, face_map) = self.append(other);
+        let boundary_b = edge_map.get_by_left(&boundary_b).copied().unwrap();
+        let loop_b = [vec![boundary_b], self.nexts(boundary_b)].concat();
+
+        // Pair the boundary edges: a_i (u_i -> u_i+1) with b_-i (w_-i -> w_-i+1), where u_i = w_-i+1 and u_i+1 = w_-i.
+        let pairs = (0..n).map(|i| (loop_a[i], loop_b[(n - i) % n])).collect_vec();
+
+        // Merge the vertices of the other mesh into those of this mesh.
+        for &(e_a, e_b) in &pairs {
+            let (v_a, v_b) = (self.toor(e_a), self.root(e_b));
+            for edge_id in self.outgoing(v_b) {
+                self.set_root(edge_id, v_a);
+            }
+            self.remove_vertex(v_b);
+            let other_id = vert_map.get_by_right(&v_b).copied().unwrap();
+            vert_map.insert(other_id, v_a);
+        }
+
+        // Replace the paired boundary edges, by making their twins twins of each other.
+        for (e_a, e_b) in pairs {
+            let (t_a, t_b) = (self.twin(e_a), self.twin(e_b));
+            self.set_twin(t_a, t_b);
+            self.set_twin(t_b, t_a);
+            self.set_vert_rep(self.root(t_a), t_a);
+            self.set_vert_rep(self.root(t_b), t_b);
+            self.remove_edge(e_a);
+            self.remove_edge(e_b);
+            let other_id = edge_map.get_by_right(&e_b).copied().unwrap();
+            edge_map.insert(other_id, t_a);
+        }
+
+        Ok((vert_map, edge_map, face_map))
+    }
+}
+
+impl<VertID: Key, V: Default + Clone, EdgeID: Key, E: Default + Clone, FaceID: Key, F: Default + Clone> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Construct a new DCEL from the given faces of this mesh, copying the data of all its elements.
+    // Returns the new mesh, and the mappings from its vertices, edges, and faces (left) to those of this mesh (right).
+    // Edges on the boundary of the new mesh are mapped to the corresponding (half)edges of this mesh, even if these are not on the boundary here.
+    fn extract(
+        &self,
+        face_ids: &[FaceID],
+        options: ConstructionOptions,
+    ) -> Result<(Self, BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        let vert_to_index = face_ids
+            .iter()
+            .flat_map(|&face_id| self.corners_iter(face_id))
+            .unique()
+            .enumerate()
+            .map(|(index, vert_id)| (vert_id, index))
+            .collect::<HashMap<_, _>>();
+        let faces = face_ids
+            .iter()
+            .map(|&face_id| self.corners_iter(face_id).map(|vert_id| vert_to_index[&vert_id]).collect_vec())
+            .collect_vec();
+
+        let (mut child, child_vert_map, child_face_map, _) = Self::from_faces_with(&faces, options)?;
+
+        let mut vert_map = BiHashMap::new();
+        for (&vert_id, &index) in &vert_to_index {
+            let child_vert_id = child_vert_map.get_by_left(&index).copied().unwrap();
+            child.verts[child_vert_id] = self.verts[vert_id].clone();
+            vert_map.insert(child_vert_id, vert_id);
+        }
+
+        let mut face_map = BiHashMap::new();
+        for (index, &face_id) in face_ids.iter().enumerate() {
+            let child_face_id = child_face_map.get_by_left(&index).copied().unwrap();
+            child.faces[child_face_id] = self.faces[face_id].clone();
+            face_map.insert(child_face_id, face_id);
+        }
+
+        let mut edge_map = BiHashMap::new();
+        for child_edge_id in child.edge_ids() {
+            let (u, v) = child.endpoints(child_edge_id);
+            let (&u, &v) = (vert_map.get_by_left(&u).unwrap(), vert_map.get_by_left(&v).unwrap());
+            if let Some((edge_id, _)) = self.edge_between_verts(u, v) {
+                child.edges[child_edge_id] = self.edges[edge_id].clone();
+                edge_map.insert(child_edge_id, edge_id);
+            } else {
+                return Err(MeshError::MissingEdge(u, v));
+            }
+        }
+
+        Ok((child, vert_map, edge_map, face_map))
+    }
+
+    // Splits the mesh into its (connected) components, each as a separate DCEL with a copy of the data of its elements.
+    // Returns, for each component, the new mesh and the mappings from its vertices, edges, and faces (left) to those of this mesh (right).
+    pub fn split_components(
+        &self,
+    ) -> Result<Vec<(Self, BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>)>, MeshError<VertID, EdgeID, FaceID>> {
+        self.components()
+            .into_iter()
+            .map(|(_, _, faces)| self.extract(&faces.into_iter().collect_vec(), ConstructionOptions::default()))
+            .collect()
+    }
+
+    // Construct a new DCEL from the given selection of faces, with a copy of the data of its elements.
+    // The selection may have boundary, and may consist of multiple components.
+    // Returns the new mesh, and the mappings from its vertices, edges, and faces (left) to those of this mesh (right), as in `split_components`.
+    // Returns an error if a selected face does not exist, or if the selection is not a 2-manifold (e.g. two selected faces only touch at a vertex).
+    pub fn submesh(
+        &self,
+        face_ids: &HashSet<FaceID>,
+    ) -> Result<(Self, BiHashMap<VertID, VertID>, BiHashMap<EdgeID, EdgeID>, BiHashMap<FaceID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        if let Some(&face_id) = face_ids.iter().find(|&&face_id| !self.faces.contains_key(face_id)) {
+            return Err(MeshError::InvalidFace(face_id));
+        }
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            ..ConstructionOptions::default()
+        };
+        self.extract(&face_ids.iter().copied().collect_vec(), options)
     }
 }
@@ -1,4 +1,4 @@
-use crate::douconel::{Douconel, MeshError};
+use crate::douconel::{AttributePolicy, ConstructionOptions, ConstructionReport, Douconel, MeshError};
 use bimap::BiHashMap;
 use bvh::{
     aabb::{Aabb, Bounded},
@@ -10,12 +10,17 @@ use hutspot::geom::Vector2D;
 use itertools::Itertools;
 use kdtree::{KdTree, distance::squared_euclidean};
 use ordered_float::OrderedFloat;
+use rand::{
+    Rng,
+    distr::{Distribution, weighted::WeightedIndex},
+};
 use serde::{Deserialize, Serialize};
 use slotmap::Key;
+use std::collections::HashMap;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub enum EmbeddedMeshError<VertID, FaceID> {
+pub enum EmbeddedMeshError<VertID, EdgeID, FaceID> {
     #[error("{0} is not a polygon (less than 3 vertices)")]
     FaceNotPolygon(FaceID),
     #[error("{0} is not planar (vertices are not coplanar)")]
@@ -23,10 +28,10 @@ pub enum EmbeddedMeshError<VertID, FaceID> {
     #[error("{0} is not simple (edges intersect)")]
     FaceNotSimple(FaceID),
     #[error("{0:?}")]
-    MeshError(MeshError<VertID>),
+    MeshError(MeshError<VertID, EdgeID, FaceID>),
 }
 
-type Float = f64;
+pub(crate) type Float = f64;
 type Vector3D = nalgebra::SVector<Float, 3>;
 const PI: f64 = std::f64::consts::PI;
 
@@ -50,10 +55,38 @@ impl HasPosition for EmbeddedVertex {
     }
 }
 
+// Policy for the editing operations (see `AttributePolicy`) that places new vertices at the linear interpolation of the positions of their parents.
+// All other data of a new vertex is copied from the nearer parent.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct InterpolatePosition;
+
+impl<V: Default + HasPosition + Clone, E: Default, F: Clone> AttributePolicy<V, E, F> for InterpolatePosition {
+    fn interpolate_vertex(&self, a: &V, b: &V, t: f64) -> V {
+        let mut vert = if t <= 0.5 { a.clone() } else { b.clone() };
+        vert.set_position(a.position().lerp(&b.position(), t));
+        vert
+    }
+}
+
+// The policy that is used by `splip_edge`: new elements get default data, and new vertices (only) an interpolated position.
+struct InterpolatePositionOnly;
+
+impl<V: Default + HasPosition, E: Default, F: Default + Clone> AttributePolicy<V, E, F> for InterpolatePositionOnly {
+    fn interpolate_vertex(&self, a: &V, b: &V, t: f64) -> V {
+        let mut vert = V::default();
+        vert.set_position(a.position().lerp(&b.position(), t));
+        vert
+    }
+
+    fn split_face_attr(&self, _parent: &F) -> F {
+        F::default()
+    }
+}
+
 impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
     // This is a struct that defines an embedded mesh with vertices (with position), edges, and faces (with clockwise ordering).
     // This embedded mesh is:
-    //      a closed 2-manifold: Each edge corresponds to exactly two faces.
+    //      a 2-manifold (possibly with boundary): Each edge corresponds to one or two faces.
     //      connected: There exists a path between any two vertices.
     //      orientable: There exists a consistent normal for each face.
     //      polygonal: Each face is a simple polygon (lies in a plane, no intersections).
@@ -61,9 +94,39 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
     pub fn from_embedded_faces(
         faces: &[Vec<usize>],
         vertex_positions: &[Vector3D],
-    ) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), EmbeddedMeshError<VertID, FaceID>> {
-        let non_embedded = Self::from_faces(faces);
-        if let Ok((mut douconel, vertex_map, face_map)) = non_embedded {
+    ) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
+        Self::from_embedded_faces_with(faces, vertex_positions, ConstructionOptions::default()).map(|(mesh, vert_map, face_map, _)| (mesh, vert_map, face_map))
+    }
+
+    // Construct an embedded DCEL from a list of faces and vertex positions, with the given construction options.
+    // Also returns a report of the changes that were made to the input faces (see `from_faces_with`).
+    pub fn from_embedded_faces_with(
+        faces: &[Vec<usize>],
+        vertex_positions: &[Vector3D],
+        options: ConstructionOptions,
+    ) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>, ConstructionReport), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
+        // The duplicated vertices get the position of their original vertex (and are reported with their original vertex index).
+        if options.split_non_manifold_vertices {
+            let (split_faces, origin) = Self::split_non_manifold_vertices(faces);
+            let split_positions = origin
+                .iter()
+                .map(|&vertex_index| vertex_positions.get(vertex_index).copied().unwrap_or_else(Vector3D::zeros))
+                .collect_vec();
+            let options = ConstructionOptions {
+                split_non_manifold_vertices: false,
+                ..options
+            };
+            return Self::from_embedded_faces_with(&split_faces, &split_positions, options).map(|(mesh, vert_map, face_map, report)| {
+                let report = ConstructionReport {
+                    vertex_origin: origin,
+                    ..report
+                };
+                (mesh, vert_map, face_map, report)
+            });
+        }
+
+        let non_embedded = Self::from_faces_with(faces, options);
+        if let Ok((mut douconel, vertex_map, face_map, report)) = non_embedded {
             for (inp_vertex_id, inp_vertex_position) in vertex_positions.iter().copied().enumerate() {
                 let vertex_id = vertex_map.get_by_left(&inp_vertex_id).copied().unwrap();
                 if let Some(vert) = douconel.verts.get_mut(vertex_id) {
@@ -111,7 +174,7 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
                 }
             }
 
-            Ok((douconel, vertex_map, face_map))
+            Ok((douconel, vertex_map, face_map, report))
         } else {
             non_embedded.map_err(EmbeddedMeshError::MeshError)
         }
@@ -123,12 +186,62 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
         self.verts.get(id).unwrap_or_else(|| panic!("V:{id:?} not initialized")).position()
     }
 
+    // Non-panicking variants of the geometric accessors below (see `try_root` etc.).
+    // Return `MeshError::InvalidVertex`, `MeshError::InvalidEdge`, or `MeshError::InvalidFace` if an element (or one of its references) does not exist.
+
+    pub fn try_position(&self, id: VertID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        self.verts.get(id).map(HasPosition::position).ok_or(MeshError::InvalidVertex(id))
+    }
+
+    pub fn try_centroid(&self, id: FaceID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        let positions = self
+            .try_corners(id)?
+            .into_iter()
+            .map(|vert_id| self.try_position(vert_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hutspot::math::calculate_average_f64(positions.into_iter()))
+    }
+
+    pub fn try_midpoint(&self, id: EdgeID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        Ok(self.try_position(self.try_root(id)?)? + self.try_vector(id)? * 0.5)
+    }
+
+    pub fn try_vector(&self, id: EdgeID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        let (u, v) = self.try_endpoints(id)?;
+        Ok(self.try_position(v)? - self.try_position(u)?)
+    }
+
+    pub fn try_length(&self, id: EdgeID) -> Result<Float, MeshError<VertID, EdgeID, FaceID>> {
+        Ok(self.try_vector(id)?.magnitude())
+    }
+
+    pub fn try_vector_area(&self, id: FaceID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        let positions = self
+            .try_corners(id)?
+            .into_iter()
+            .map(|vert_id| self.try_position(vert_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Same as `vector_area`: for every edge (a, b), followed by (b, c), add (a - b) x (c - b).
+        Ok(positions
+            .iter()
+            .circular_tuple_windows()
+            .fold(Vector3D::zeros(), |sum, (a, b, c)| sum + (a - b).cross(&(c - b))))
+    }
+
+    pub fn try_area(&self, id: FaceID) -> Result<Float, MeshError<VertID, EdgeID, FaceID>> {
+        Ok(self.try_vector_area(id)?.magnitude() / 2.0)
+    }
+
+    pub fn try_normal(&self, id: FaceID) -> Result<Vector3D, MeshError<VertID, EdgeID, FaceID>> {
+        Ok(-self.try_vector_area(id)?.normalize())
+    }
+
     // Get centroid of a given polygonal face.
     // https://en.wikipedia.org/wiki/Centroid
     // Be careful with concave faces, the centroid might lay outside the face.
     #[must_use]
     pub fn centroid(&self, face_id: FaceID) -> Vector3D {
-        hutspot::math::calculate_average_f64(self.edges(face_id).iter().map(|&edge_id| self.position(self.root(edge_id))))
+        hutspot::math::calculate_average_f64(self.corners_iter(face_id).map(|vert_id| self.position(vert_id)))
     }
 
     // Get midpoint of a given edge.
@@ -179,10 +292,14 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
 
     // Get angular defect of a vertex (2PI - C, where C = the sum of all the angles at the vertex).
     // See https://en.wikipedia.org/wiki/Angular_defect
+    // For boundary vertices, the (missing) angle of the boundary is not part of C.
     #[must_use]
     pub fn defect(&self, id: VertID) -> Float {
-        let sum_of_angles = self.outgoing(id).iter().fold(0., |sum, &outgoing_edge_id| {
+        let sum_of_angles = self.outgoing_iter(id).fold(0., |sum, outgoing_edge_id| {
             let incoming_edge_id = self.twin(outgoing_edge_id);
+            if self.is_boundary(incoming_edge_id) {
+                return sum;
+            }
             let next_edge_id = self.next(incoming_edge_id);
             let angle = self.angle(outgoing_edge_id, next_edge_id);
             sum + angle
@@ -195,7 +312,7 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
     // Vector area of a given face.
     #[must_use]
     pub fn vector_area(&self, id: FaceID) -> Vector3D {
-        self.edges(id).iter().fold(Vector3D::zeros(), |sum, &edge_id| {
+        self.edges_iter(id).fold(Vector3D::zeros(), |sum, edge_id| {
             let u = self.vector(self.twin(edge_id));
             let v = self.vector(self.next(edge_id));
             sum + u.cross(&v)
@@ -208,6 +325,66 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
         self.vector_area(id).magnitude() / 2.0
     }
 
+    // Return `n` uniformly distributed random points on the surface.
+    // Every face is split into a fan of triangles (from its first corner), and triangles are chosen with probability proportional to their area. Assumes planar, convex faces.
+    // Every point is given as a face, and its barycentric coordinates: a weight for every corner of the face (see `surface_point`).
+    #[must_use]
+    pub fn random_surface_points(&self, n: usize) -> Vec<(FaceID, Vec<Float>)> {
+        self.random_surface_points_with(n, &mut rand::rng())
+    }
+
+    // Return `n` random points on the surface (see `random_surface_points`), drawn from the given random number generator. (reproducible with a seeded generator)
+    // Returns no points if the mesh has no faces, or if all faces have zero area.
+    pub fn random_surface_points_with(&self, n: usize, rng: &mut impl Rng) -> Vec<(FaceID, Vec<Float>)> {
+        // The fan triangles of all faces: (face, number of corners, index of the second corner of the triangle, area).
+        let triangles = self
+            .face_ids()
+            .into_iter()
+            .flat_map(|face_id| {
+                let corners = self.corners(face_id);
+                let origin = self.position(corners[0]);
+                (1..corners.len() - 1)
+                    .map(|i| {
+                        let area = (self.position(corners[i]) - origin)
+                            .cross(&(self.position(corners[i + 1]) - origin))
+                            .magnitude()
+                            / 2.;
+                        (face_id, corners.len(), i, area)
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let Ok(distribution) = WeightedIndex::new(triangles.iter().map(|&(_, _, _, area)| area)) else {
+            return vec![];
+        };
+
+        (0..n)
+            .map(|_| {
+                let (face_id, nr_corners, i, _) = triangles[distribution.sample(rng)];
+                // Uniform point in the triangle: reflect points of the unit square that fall outside the triangle.
+                let (mut u, mut v) = (rng.random::<Float>(), rng.random::<Float>());
+                if u + v > 1. {
+                    (u, v) = (1. - u, 1. - v);
+                }
+                let mut barycentric = vec![0.; nr_corners];
+                barycentric[0] = 1. - u - v;
+                barycentric[i] = u;
+                barycentric[i + 1] = v;
+                (face_id, barycentric)
+            })
+            .collect()
+    }
+
+    // Position of the point with the given barycentric coordinates in a given face: the weighted sum of its corners (in order).
+    // Missing weights are zero.
+    #[must_use]
+    pub fn surface_point(&self, id: FaceID, barycentric: &[Float]) -> Vector3D {
+        self.corners_iter(id)
+            .zip(barycentric)
+            .map(|(vert_id, &weight)| self.position(vert_id) * weight)
+            .sum()
+    }
+
     // Get normal of face `id`. Assumes the face is planar. If the face is not planar, then this function will not return the correct normal.
     // The normal is calculated as the normalized vector area of the face; https://en.wikipedia.org/wiki/Normal_(geometry)
     #[must_use]
@@ -218,14 +395,18 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
     // Get the average normals around vertex `id`.
     #[must_use]
     pub fn vert_normal(&self, id: VertID) -> Vector3D {
-        self.star(id).iter().map(|&face_id| self.normal(face_id)).sum::<Vector3D>().normalize()
+        self.star_iter(id).map(|face_id| self.normal(face_id)).sum::<Vector3D>().normalize()
     }
 
-    // Get the normal of edge `id` by averaging the normals of the two faces it belongs to.
+    // Get the normal of edge `id` by averaging the normals of the faces it belongs to (one face for boundary edges).
     #[must_use]
     pub fn edge_normal(&self, id: EdgeID) -> Vector3D {
-        let [f1, f2] = self.faces(id);
-        (self.normal(f1) + self.normal(f2)).normalize()
+        [id, self.twin(id)]
+            .into_iter()
+            .filter(|&edge_id| !self.is_boundary(edge_id))
+            .map(|edge_id| self.normal(self.face(edge_id)))
+            .sum::<Vector3D>()
+            .normalize()
     }
 
     // Get the angle between two edges at a common vertex.
@@ -236,10 +417,10 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
         self.vec_angle(a_b, b_c)
     }
 
-    // List of all edges in the mesh (positions of endpoints)
+    // List of all (undirected) edges in the mesh (positions of endpoints), one per pair of twins.
     #[must_use]
     pub fn edges_positions(&self) -> Vec<(Vector3D, Vector3D)> {
-        self.edge_ids()
+        self.undirected_edges()
             .iter()
             .map(|&edge_id| {
                 let (u, v) = self.endpoints(edge_id);
@@ -332,6 +513,15 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
         let (a1, a2) = (self.wedge_alpha((b, &w1)), self.wedge_alpha((b, &w2)));
         if a1 < a2 { (w1, a1) } else { (w2.into_iter().rev().collect_vec(), a2) }
     }
+
+    // Construct the dual of the mesh (see `dual`), with every dual vertex placed at the centroid of its primal face.
+    pub fn dual_embedded(&self) -> Result<(Self, BiHashMap<FaceID, VertID>, BiHashMap<VertID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        let (mut dual, face_to_dual, vert_to_dual) = self.dual()?;
+        for (&face_id, &dual_vert_id) in &face_to_dual {
+            dual.verts[dual_vert_id].set_position(self.centroid(face_id));
+        }
+        Ok((dual, face_to_dual, vert_to_dual))
+    }
 }
 
 // implement default for KdTree using the New Type Idiom
@@ -450,7 +640,7 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
     }
 }
 
-impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key, F: Default + Clone> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+impl<VertID: Key, V: Default + HasPosition + Clone, EdgeID: Key, E: Default, FaceID: Key, F: Default + Clone> Douconel<VertID, V, EdgeID, E, FaceID, F> {
     pub fn splip_edge(&mut self, a: VertID, b: VertID) -> Option<VertID> {
         // Make sure the edge exists
         let edge = self.edge_between_verts(a, b).unwrap().0;
@@ -545,28 +735,211 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
             return Some(b);
         }
 
-        // Calculate the position of the split vertex in 3D
-        let split_position = self.position(a) + (self.position(b) - self.position(a)) * t;
-
-        // Split edge a_b
-        let (split_vertex, _) = self.split_edge(a_b);
+        // Split edge a_b (at the intersection). The new elements get default data.
+        let (split_vertex, _) = self.split_edge_at_with(a_b, t, &InterpolatePositionOnly);
 
         // There exists an edge between c1 and split_vertex and c2 and split_vertex
         assert!(self.edge_between_verts(c1, split_vertex).is_some());
         assert!(self.edge_between_verts(c2, split_vertex).is_some());
 
-        // Move the split vertex to the correct position
-        self.verts.get_mut(split_vertex).unwrap().set_position(split_position);
-
         return Some(split_vertex);
     }
 
+    // Splits the given edge at parameter `t` (from its root at t = 0, to its end at t = 1), by inserting a new vertex at the linear interpolation of its endpoints.
+    // Adjacent triangles are split in two, by connecting the new vertex to their opposite corner (unless they are already connected).
+    // The new vertex gets the data of the nearer endpoint (see `InterpolatePosition`), and the new faces a copy of the face they are split from.
+    // Other adjacent faces are not re-triangulated, they get the new vertex as an additional corner.
+    // Returns the new vertex and the faces around it.
+    pub fn split_edge_at(&mut self, edge_id: EdgeID, t: f64) -> (VertID, Vec<FaceID>) {
+        self.split_edge_at_with(edge_id, t, &InterpolatePosition)
+    }
+
+    // Same as `split_edge_at`, with the data of the new elements given by `policy` (the new vertex is given by `policy.interpolate_vertex(root, toor, t)`).
+    pub fn split_edge_at_with(&mut self, edge_id: EdgeID, t: f64, policy: &impl AttributePolicy<V, E, F>) -> (VertID, Vec<FaceID>) {
+        // The adjacent triangles, with their opposite corner
+        let triangles = [edge_id, self.twin(edge_id)]
+            .into_iter()
+            .filter(|&e| !self.is_boundary(e) && self.edges_iter(self.face(e)).count() == 3)
+            .map(|e| (self.face(e), self.root(self.prev(e))))
+            .collect_vec();
+
+        let (v_x, _) = self.subdivide_edge_with(edge_id, t, policy);
+        for (face_id, v_c) in triangles {
+            // Both triangles can have the same opposite corner (e.g., on a closed mesh of two triangles): the second triangle is then not split.
+            if self.edge_between_verts(v_x, v_c).is_some() {
+                continue;
+            }
+            // The new vertex is only connected to the endpoints of the edge, so it is never adjacent to the opposite corner.
+            self.split_face_by_diagonal_with(face_id, v_x, v_c, policy)
+                .expect("the new vertex and the opposite corner of a triangle are non-adjacent and not connected");
+        }
+
+        (v_x, self.star(v_x))
+    }
+
     pub fn refine(&mut self, n: usize) {
         for _ in 0..n {
-            // find the longest edge
-            let longest_edge = self.edges.keys().max_by_key(|&edge_id| OrderedFloat(self.length(edge_id))).unwrap();
+            // find the longest (non-boundary) edge
+            let Some(longest_edge) = self
+                .edges
+                .keys()
+                .filter(|&edge_id| !self.is_boundary_edge(edge_id))
+                .max_by_key(|&edge_id| OrderedFloat(self.length(edge_id)))
+            else {
+                return;
+            };
             let (a, b) = self.endpoints(longest_edge);
             self.splip_edge(a, b);
         }
     }
 }
+
+impl<VertID: Key, V: Default + HasPosition + Clone, EdgeID: Key, E: Default + Clone, FaceID: Key, F: Default + Clone>
+    Douconel<VertID, V, EdgeID, E, FaceID, F>
+{
+    // Catmull-Clark subdivision, applied `levels` times. https://en.wikipedia.org/wiki/Catmull%E2%80%93Clark_subdivision_surface
+    // Works for faces of any degree, and results in a pure quad mesh (after at least one level). Boundaries are subdivided as cubic B-splines.
+    // Boundary corners (boundary vertices with valence 2) are kept fixed, as creases.
+    // The original vertices keep their data (with a new position), the halves of the original edges keep the data of their edge, and new faces inherit the data of their parent face.
+    // New vertices and edges get default data (and a position). Property layers are carried over in the same way.
+    // Returns the subdivided mesh, a map from the original vertices (left) to the subdivided vertices (right), and a map from every new face to its parent face in the original mesh.
+    pub fn catmull_clark(
+        &self,
+        levels: usize,
+    ) -> Result<(Self, BiHashMap<VertID, VertID>, HashMap<FaceID, FaceID>), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
+        let mut subdivided = self.clone();
+        let mut vert_map = self.verts.keys().map(|vert_id| (vert_id, vert_id)).collect::<BiHashMap<_, _>>();
+        let mut parents = self.faces.keys().map(|face_id| (face_id, face_id)).collect::<HashMap<_, _>>();
+        for _ in 0..levels {
+            let (next, next_vert_map, next_parents) = subdivided.catmull_clark_step()?;
+            vert_map = vert_map
+                .into_iter()
+                .map(|(vert_id, cur_id)| (vert_id, next_vert_map.get_by_left(&cur_id).copied().unwrap()))
+                .collect();
+            parents = next_parents.into_iter().map(|(face_id, parent_id)| (face_id, parents[&parent_id])).collect();
+            subdivided = next;
+        }
+        Ok((subdivided, vert_map, parents))
+    }
+
+    // A single level of Catmull-Clark subdivision. Returns the subdivided mesh, a map from the vertices to the subdivided vertices, and a map from every new face to its parent face.
+    fn catmull_clark_step(&self) -> Result<(Self, BiHashMap<VertID, VertID>, HashMap<FaceID, FaceID>), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
+        // The new vertices are: the (moved) original vertices, a face point per face, and an edge point per (undirected) edge.
+        let vert_ids = self.vert_ids();
+        let face_ids = self.face_ids();
+        let edge_ids = self.undirected_edges();
+        let vert_to_index = vert_ids.iter().enumerate().map(|(index, &vert_id)| (vert_id, index)).collect::<HashMap<_, _>>();
+        let face_to_index = face_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &face_id)| (face_id, vert_ids.len() + index))
+            .collect::<HashMap<_, _>>();
+        let edge_to_index = edge_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &edge_id)| (edge_id, vert_ids.len() + face_ids.len() + index))
+            .collect::<HashMap<_, _>>();
+
+        // Face point: the centroid of the face.
+        let face_points = face_ids.iter().map(|&face_id| (face_id, self.centroid(face_id))).collect::<HashMap<_, _>>();
+
+        // Edge point: the average of the endpoints and the two adjacent face points (or the midpoint, on the boundary).
+        let edge_point = |edge_id: EdgeID| {
+            if self.is_boundary_edge(edge_id) {
+                self.midpoint(edge_id)
+            } else {
+                let (u, v) = self.endpoints(edge_id);
+                (self.position(u) + self.position(v) + face_points[&self.face(edge_id)] + face_points[&self.face(self.twin(edge_id))]) / 4.
+            }
+        };
+
+        // Vertex point: (Q + 2R + (n - 3)P) / n, with Q the average of the adjacent face points, R the average of the midpoints of the adjacent edges, P the original position, and n the valence.
+        // On the boundary: (6P + A + B) / 8, with A and B the neighbors along the boundary. Boundary corners (valence 2) stay at P.
+        let vertex_point = |vert_id: VertID| {
+            let p = self.position(vert_id);
+            if self.is_boundary_vertex(vert_id) {
+                if self.valence(vert_id) == 2 {
+                    return p;
+                }
+                let boundary_neighbors = self
+                    .outgoing_iter(vert_id)
+                    .filter(|&edge_id| self.is_boundary_edge(edge_id))
+                    .map(|edge_id| self.position(self.toor(edge_id)))
+                    .collect_vec();
+                if let [a, b] = boundary_neighbors[..] { (6. * p + a + b) / 8. } else { p }
+            } else {
+                let n = Float::from(u32::try_from(self.valence(vert_id)).unwrap());
+                let q = hutspot::math::calculate_average_f64(self.star_iter(vert_id).map(|face_id| face_points[&face_id]));
+                let r = hutspot::math::calculate_average_f64(self.outgoing_iter(vert_id).map(|edge_id| self.midpoint(edge_id)));
+                (q + 2. * r + (n - 3.) * p) / n
+            }
+        };
+
+        let positions = vert_ids
+            .iter()
+            .map(|&vert_id| vertex_point(vert_id))
+            .chain(face_ids.iter().map(|face_id| face_points[face_id]))
+            .chain(edge_ids.iter().map(|&edge_id| edge_point(edge_id)))
+            .collect_vec();
+
+        // Every face is split into a quad per corner: the corner, the edge point of its outgoing edge, the face point, and the edge point of its incoming edge.
+        let mut faces = vec![];
+        let mut parents = vec![];
+        for &face_id in &face_ids {
+            for (incoming, outgoing) in self.edges(face_id).into_iter().circular_tuple_windows() {
+                faces.push(vec![
+                    vert_to_index[&self.root(outgoing)],
+                    edge_to_index[&self.canonical(outgoing)],
+                    face_to_index[&face_id],
+                    edge_to_index[&self.canonical(incoming)],
+                ]);
+                parents.push(face_id);
+            }
+        }
+
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            ..ConstructionOptions::default()
+        };
+        let (mut subdivided, index_map, face_map, _) = Self::from_embedded_faces_with(&faces, &positions, options)?;
+        let new_vert = |index: usize| index_map.get_by_left(&index).copied().unwrap();
+
+        // The original vertices keep their data, with their new position.
+        let mut vert_map = BiHashMap::new();
+        for (index, &vert_id) in vert_ids.iter().enumerate() {
+            let new_vert_id = new_vert(index);
+            let position = subdivided.position(new_vert_id);
+            subdivided.verts[new_vert_id] = self.verts[vert_id].clone();
+            subdivided.verts[new_vert_id].set_position(position);
+            vert_map.insert(vert_id, new_vert_id);
+        }
+
+        // Every half-edge is split into two halves (through its edge point), which keep its data.
+        let mut edge_to_parent = HashMap::new();
+        for edge_id in self.edges.keys() {
+            let (u, v) = self.endpoints(edge_id);
+            let edge_point = new_vert(edge_to_index[&self.canonical(edge_id)]);
+            for (a, b) in [(new_vert(vert_to_index[&u]), edge_point), (edge_point, new_vert(vert_to_index[&v]))] {
+                let (half_id, _) = subdivided
+                    .edge_between_verts(a, b)
+                    .expect("every half-edge is split into two halves through its edge point");
+                subdivided.edges[half_id] = self.edges[edge_id].clone();
+                edge_to_parent.insert(half_id, edge_id);
+            }
+        }
+
+        let mut face_to_parent = HashMap::new();
+        for (index, parent_id) in parents.into_iter().enumerate() {
+            let face_id = face_map.get_by_left(&index).copied().unwrap();
+            subdivided.faces[face_id] = self.faces[parent_id].clone();
+            face_to_parent.insert(face_id, parent_id);
+        }
+
+        let vert_to_parent = vert_map.iter().map(|(&vert_id, &new_vert_id)| (new_vert_id, vert_id)).collect();
+        subdivided.vert_props = self.vert_props.inherit(&subdivided.vert_ids(), &vert_to_parent);
+        subdivided.edge_props = self.edge_props.inherit(&subdivided.edge_ids(), &edge_to_parent);
+        subdivided.face_props = self.face_props.inherit(&subdivided.face_ids(), &face_to_parent);
+
+        Ok((subdivided, vert_map, face_to_parent))
+    }
+}
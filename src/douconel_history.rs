@@ -0,0 +1,259 @@
+use crate::{
+    douconel::{Douconel, MeshError},
+    douconel_properties::Properties,
+};
+use slotmap::{Key, SecondaryMap, SlotMap};
+use std::fmt::Debug;
+
+// The change log of one of the internal maps: every changed key with its value before the change (None if it had no value), in order of change.
+pub(crate) type Log<K, T> = Vec<(K, Option<T>)>;
+
+// The changes of one transaction.
+// The internal maps (edge_root, edge_face, edge_next, edge_twin, vert_rep, face_rep) have a change log, which is replayed in reverse to revert them.
+// The SlotMaps and property layers are copied before their first change (copy-on-write), and the copy is restored to revert them.
+// A SlotMap cannot reinsert a removed key with its version, so restoring the exact same IDs requires a copy of the SlotMap.
+pub(crate) struct Changes<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> {
+    pub(crate) edge_root: Log<EdgeID, VertID>,
+    pub(crate) edge_face: Log<EdgeID, FaceID>,
+    pub(crate) edge_next: Log<EdgeID, EdgeID>,
+    pub(crate) edge_twin: Log<EdgeID, EdgeID>,
+    pub(crate) vert_rep: Log<VertID, EdgeID>,
+    pub(crate) face_rep: Log<FaceID, EdgeID>,
+    verts: Option<SlotMap<VertID, V>>,
+    edges: Option<SlotMap<EdgeID, E>>,
+    faces: Option<SlotMap<FaceID, F>>,
+    vert_props: Option<Properties<VertID>>,
+    edge_props: Option<Properties<EdgeID>>,
+    face_props: Option<Properties<FaceID>>,
+    // Set by `begin` (which requires the data to be cloneable), so the helpers that edit the SlotMaps do not require it.
+    clone_verts: fn(&SlotMap<VertID, V>) -> SlotMap<VertID, V>,
+    clone_edges: fn(&SlotMap<EdgeID, E>) -> SlotMap<EdgeID, E>,
+    clone_faces: fn(&SlotMap<FaceID, F>) -> SlotMap<FaceID, F>,
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Changes<VertID, V, EdgeID, E, FaceID, F> {
+    // Copies the vertices, unless they were already copied in this transaction.
+    pub(crate) fn save_verts(&mut self, verts: &SlotMap<VertID, V>) {
+        self.verts.get_or_insert_with(|| (self.clone_verts)(verts));
+    }
+
+    // Copies the edges, unless they were already copied in this transaction.
+    pub(crate) fn save_edges(&mut self, edges: &SlotMap<EdgeID, E>) {
+        self.edges.get_or_insert_with(|| (self.clone_edges)(edges));
+    }
+
+    // Copies the faces, unless they were already copied in this transaction.
+    pub(crate) fn save_faces(&mut self, faces: &SlotMap<FaceID, F>) {
+        self.faces.get_or_insert_with(|| (self.clone_faces)(faces));
+    }
+
+    // Copies the vertex property layers, unless they were already copied in this transaction.
+    pub(crate) fn save_vert_props(&mut self, props: &Properties<VertID>) {
+        self.vert_props.get_or_insert_with(|| props.clone());
+    }
+
+    // Copies the edge property layers, unless they were already copied in this transaction.
+    pub(crate) fn save_edge_props(&mut self, props: &Properties<EdgeID>) {
+        self.edge_props.get_or_insert_with(|| props.clone());
+    }
+
+    // Copies the face property layers, unless they were already copied in this transaction.
+    pub(crate) fn save_face_props(&mut self, props: &Properties<FaceID>) {
+        self.face_props.get_or_insert_with(|| props.clone());
+    }
+}
+
+// Sets (or removes, for None) the value of a key, and returns its old value.
+fn set<K: Key, T>(map: &mut SecondaryMap<K, T>, key: K, value: Option<T>) -> Option<T> {
+    match value {
+        Some(value) => map.insert(key, value),
+        None => map.remove(key),
+    }
+}
+
+// Sets (or removes, for None) the value of a key of an internal map, and records its old value in the given log (if any).
+pub(crate) fn record<K: Key, T>(map: &mut SecondaryMap<K, T>, log: Option<&mut Log<K, T>>, key: K, value: Option<T>) {
+    let old = set(map, key, value);
+    if let Some(log) = log {
+        log.push((key, old));
+    }
+}
+
+// Replaces all values of an internal map, and records the old value of every changed key in the given log (if any).
+pub(crate) fn record_all<K: Key, T>(map: &mut SecondaryMap<K, T>, mut log: Option<&mut Log<K, T>>, values: SecondaryMap<K, T>) {
+    for key in map.keys().collect::<Vec<_>>() {
+        record(map, log.as_deref_mut(), key, None);
+    }
+    for (key, value) in values {
+        record(map, log.as_deref_mut(), key, Some(value));
+    }
+}
+
+// Reverts the changes of a log (in reverse order), and returns the log that reapplies them.
+fn revert_log<K: Key, T>(map: &mut SecondaryMap<K, T>, log: Log<K, T>) -> Log<K, T> {
+    log.into_iter().rev().map(|(key, old)| (key, set(map, key, old))).collect()
+}
+
+// The transaction log of a mesh: the open transaction, and the undo and redo stacks.
+// Every entry holds the changes of a committed transaction: to revert it (undo), or to reapply it (redo).
+// Memory grows with the number of changed keys, plus the size of every SlotMap and property layer that was changed (see `clear_history`).
+pub struct History<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> {
+    open: Option<Changes<VertID, V, EdgeID, E, FaceID, F>>,
+    undo: Vec<Changes<VertID, V, EdgeID, E, FaceID, F>>,
+    redo: Vec<Changes<VertID, V, EdgeID, E, FaceID, F>>,
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> History<VertID, V, EdgeID, E, FaceID, F> {
+    // Returns the changes of the open transaction, to record an edit in.
+    // Without an open transaction the edit is not recorded, and the undo and redo stacks are cleared (their changes no longer apply to the edited mesh).
+    pub(crate) fn changes(&mut self) -> Option<&mut Changes<VertID, V, EdgeID, E, FaceID, F>> {
+        if self.open.is_none() {
+            self.undo.clear();
+            self.redo.clear();
+        }
+        self.open.as_mut()
+    }
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Default for History<VertID, V, EdgeID, E, FaceID, F> {
+    fn default() -> Self {
+        Self {
+            open: None,
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+}
+
+// A clone of a mesh starts with an empty history: the history belongs to the mesh it was recorded on.
+// This keeps clones (e.g., in `dual`, `extract`, and `catmull_clark`) from copying the undo and redo stacks.
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Clone for History<VertID, V, EdgeID, E, FaceID, F> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Debug for History<VertID, V, EdgeID, E, FaceID, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("History")
+            .field("open", &self.open.is_some())
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .finish()
+    }
+}
+
+impl<VertID: Key, V: Default + Clone, EdgeID: Key, E: Default + Clone, FaceID: Key, F: Default + Clone> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Reverts the given changes, and returns the changes that reapply them.
+    fn revert(&mut self, changes: Changes<VertID, V, EdgeID, E, FaceID, F>) -> Changes<VertID, V, EdgeID, E, FaceID, F> {
+        Changes {
+            edge_root: revert_log(&mut self.edge_root, changes.edge_root),
+            edge_face: revert_log(&mut self.edge_face, changes.edge_face),
+            edge_next: revert_log(&mut self.edge_next, changes.edge_next),
+            edge_twin: revert_log(&mut self.edge_twin, changes.edge_twin),
+            vert_rep: revert_log(&mut self.vert_rep, changes.vert_rep),
+            face_rep: revert_log(&mut self.face_rep, changes.face_rep),
+            verts: changes.verts.map(|verts| std::mem::replace(&mut self.verts, verts)),
+            edges: changes.edges.map(|edges| std::mem::replace(&mut self.edges, edges)),
+            faces: changes.faces.map(|faces| std::mem::replace(&mut self.faces, faces)),
+            vert_props: changes.vert_props.map(|props| std::mem::replace(&mut self.vert_props, props)),
+            edge_props: changes.edge_props.map(|props| std::mem::replace(&mut self.edge_props, props)),
+            face_props: changes.face_props.map(|props| std::mem::replace(&mut self.face_props, props)),
+            ..changes
+        }
+    }
+
+    // Opens a transaction: all edits until `commit` (or `rollback`) form a single step that can be undone.
+    // Only edits through the methods of the mesh are recorded (not direct edits of `verts`, `edges`, and `faces`).
+    // Edits outside a transaction clear the undo and redo stacks.
+    // Returns an error if a transaction is already open.
+    pub fn begin(&mut self) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        if self.history.open.is_some() {
+            return Err(MeshError::TransactionOpen);
+        }
+        self.history.open = Some(Changes {
+            edge_root: vec![],
+            edge_face: vec![],
+            edge_next: vec![],
+            edge_twin: vec![],
+            vert_rep: vec![],
+            face_rep: vec![],
+            verts: None,
+            edges: None,
+            faces: None,
+            vert_props: None,
+            edge_props: None,
+            face_props: None,
+            clone_verts: SlotMap::clone,
+            clone_edges: SlotMap::clone,
+            clone_faces: SlotMap::clone,
+        });
+        Ok(())
+    }
+
+    // Closes the open transaction, and pushes it onto the undo stack. This clears the redo stack.
+    // Returns an error if no transaction is open.
+    pub fn commit(&mut self) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        let changes = self.history.open.take().ok_or(MeshError::NoTransaction)?;
+        self.history.undo.push(changes);
+        self.history.redo.clear();
+        Ok(())
+    }
+
+    // Closes the open transaction, and reverts all edits since `begin` (restoring the exact same IDs).
+    // Returns an error if no transaction is open.
+    pub fn rollback(&mut self) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        let changes = self.history.open.take().ok_or(MeshError::NoTransaction)?;
+        self.revert(changes);
+        Ok(())
+    }
+
+    // Reverts the last committed transaction (restoring the exact same IDs).
+    // Returns false if there is nothing to undo, or if a transaction is open.
+    pub fn undo(&mut self) -> bool {
+        if self.history.open.is_some() {
+            return false;
+        }
+        let Some(changes) = self.history.undo.pop() else {
+            return false;
+        };
+        let reapply = self.revert(changes);
+        self.history.redo.push(reapply);
+        true
+    }
+
+    // Reapplies the last undone transaction (restoring the exact same IDs).
+    // Returns false if there is nothing to redo, or if a transaction is open.
+    pub fn redo(&mut self) -> bool {
+        if self.history.open.is_some() {
+            return false;
+        }
+        let Some(changes) = self.history.redo.pop() else {
+            return false;
+        };
+        let revert = self.revert(changes);
+        self.history.undo.push(revert);
+        true
+    }
+
+    #[must_use]
+    pub const fn in_transaction(&self) -> bool {
+        self.history.open.is_some()
+    }
+
+    #[must_use]
+    pub const fn can_undo(&self) -> bool {
+        self.history.open.is_none() && !self.history.undo.is_empty()
+    }
+
+    #[must_use]
+    pub const fn can_redo(&self) -> bool {
+        self.history.open.is_none() && !self.history.redo.is_empty()
+    }
+
+    // Clears the undo and redo stacks (the open transaction, if any, stays open).
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    douconel::{Douconel, MeshError},
+    douconel::{ConstructionOptions, ConstructionReport, Douconel, MeshError},
     douconel_embedded::{EmbeddedMeshError, HasPosition},
 };
 use bimap::BiHashMap;
@@ -32,17 +32,26 @@ impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key
         Ok((verts, faces))
     }
 
-    pub fn from_file(path: &PathBuf) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), EmbeddedMeshError<VertID, FaceID>> {
+    pub fn from_file(path: &PathBuf) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
+        Self::from_file_with(path, ConstructionOptions::default()).map(|(mesh, vert_map, face_map, _)| (mesh, vert_map, face_map))
+    }
+
+    // Read an embedded DCEL from an OBJ or STL file, with the given construction options.
+    // Also returns a report of the changes that were made to the faces of the file (see `from_faces_with`).
+    pub fn from_file_with(
+        path: &PathBuf,
+        options: ConstructionOptions,
+    ) -> Result<(Self, BiHashMap<usize, VertID>, BiHashMap<usize, FaceID>, ConstructionReport), EmbeddedMeshError<VertID, EdgeID, FaceID>> {
         match OpenOptions::new().read(true).open(path) {
             Ok(file) => match path.extension().unwrap().to_str() {
                 Some("obj") => match Self::obj_to_elements(BufReader::new(file)) {
-                    Ok((verts, faces)) => Self::from_embedded_faces(&faces, &verts),
+                    Ok((verts, faces)) => Self::from_embedded_faces_with(&faces, &verts, options),
                     Err(e) => Err(EmbeddedMeshError::MeshError(MeshError::Unknown(format!(
                         "Something went wrong while reading the OBJ file: {path:?}\nErr: {e}"
                     )))),
                 },
                 Some("stl") => match Self::stl_to_elements(BufReader::new(file)) {
-                    Ok((verts, faces)) => Self::from_embedded_faces(&faces, &verts),
+                    Ok((verts, faces)) => Self::from_embedded_faces_with(&faces, &verts, options),
                     Err(e) => Err(EmbeddedMeshError::MeshError(MeshError::Unknown(format!(
                         "Something went wrong while reading the STL file: {path:?}\nErr: {e}"
                     )))),
@@ -0,0 +1,113 @@
+use crate::douconel::Douconel;
+use slotmap::Key;
+use std::iter::FusedIterator;
+
+// Lazy circulator over the outgoing edges of a vertex. (clockwise order)
+// Walks `edge_twin` and `edge_next` directly, without allocating.
+pub struct OutgoingIter<'a, VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> {
+    mesh: &'a Douconel<VertID, V, EdgeID, E, FaceID, F>,
+    stop: EdgeID,
+    cur: Option<EdgeID>,
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Iterator for OutgoingIter<'_, VertID, V, EdgeID, E, FaceID, F> {
+    type Item = EdgeID;
+
+    #[inline]
+    fn next(&mut self) -> Option<EdgeID> {
+        let cur = self.cur?;
+        let next_of_twin = self.mesh.edge_next[self.mesh.edge_twin[cur]];
+        self.cur = (next_of_twin != self.stop).then_some(next_of_twin);
+        Some(cur)
+    }
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> FusedIterator for OutgoingIter<'_, VertID, V, EdgeID, E, FaceID, F> {}
+
+// Lazy circulator over a cycle of next pointers (the edges of a face, or of a boundary loop). (anticlockwise order)
+// Walks `edge_next` directly, without allocating.
+pub struct FaceEdgeIter<'a, VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> {
+    mesh: &'a Douconel<VertID, V, EdgeID, E, FaceID, F>,
+    stop: EdgeID,
+    cur: Option<EdgeID>,
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> Iterator for FaceEdgeIter<'_, VertID, V, EdgeID, E, FaceID, F> {
+    type Item = EdgeID;
+
+    #[inline]
+    fn next(&mut self) -> Option<EdgeID> {
+        let cur = self.cur?;
+        let next = self.mesh.edge_next[cur];
+        self.cur = (next != self.stop).then_some(next);
+        Some(cur)
+    }
+}
+
+impl<VertID: Key, V, EdgeID: Key, E, FaceID: Key, F> FusedIterator for FaceEdgeIter<'_, VertID, V, EdgeID, E, FaceID, F> {}
+
+impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Returns an iterator over the outgoing edges of a given vertex. (clockwise order)
+    // Panics if the vertex has no representative edge defined.
+    #[inline]
+    #[must_use]
+    pub fn outgoing_iter(&self, id: VertID) -> OutgoingIter<'_, VertID, V, EdgeID, E, FaceID, F> {
+        let start = self.vrep(id);
+        OutgoingIter {
+            mesh: self,
+            stop: start,
+            cur: Some(start),
+        }
+    }
+
+    // Returns an iterator over the edges of a given face. (anticlockwise order)
+    // Panics if the face has no representative edge defined.
+    #[inline]
+    #[must_use]
+    pub fn edges_iter(&self, id: FaceID) -> FaceEdgeIter<'_, VertID, V, EdgeID, E, FaceID, F> {
+        let start = self.frep(id);
+        FaceEdgeIter {
+            mesh: self,
+            stop: start,
+            cur: Some(start),
+        }
+    }
+
+    // Returns an iterator over the edges following the given edge in its cycle, excluding the given edge itself.
+    #[inline]
+    #[must_use]
+    pub fn nexts_iter(&self, id: EdgeID) -> FaceEdgeIter<'_, VertID, V, EdgeID, E, FaceID, F> {
+        let next = self.next(id);
+        FaceEdgeIter {
+            mesh: self,
+            stop: id,
+            cur: (next != id).then_some(next),
+        }
+    }
+
+    // Returns an iterator over the corner vertices of a given face.
+    #[inline]
+    pub fn corners_iter(&self, id: FaceID) -> impl Iterator<Item = VertID> + '_ {
+        self.edges_iter(id).map(|edge_id| self.edge_root[edge_id])
+    }
+
+    // Returns an iterator over the faces around a given vertex. (clockwise order)
+    // Boundary edges are skipped, as they have no face.
+    #[inline]
+    pub fn star_iter(&self, id: VertID) -> impl Iterator<Item = FaceID> + '_ {
+        self.outgoing_iter(id).filter_map(|edge_id| self.edge_face.get(edge_id).copied())
+    }
+
+    // Returns an iterator over the neighbors of a given vertex.
+    #[inline]
+    pub fn vneighbors_iter(&self, id: VertID) -> impl Iterator<Item = VertID> + '_ {
+        self.outgoing_iter(id).map(|edge_id| self.edge_root[self.edge_twin[edge_id]])
+    }
+
+    // Returns an iterator over the (edge-wise) neighbors of a given face.
+    // Boundary edges are skipped, as they have no face.
+    #[inline]
+    pub fn fneighbors_iter(&self, id: FaceID) -> impl Iterator<Item = FaceID> + '_ {
+        self.edges_iter(id).filter_map(|edge_id| self.edge_face.get(self.edge_twin[edge_id]).copied())
+    }
+}
@@ -1,12 +1,13 @@
 use crate::douconel::Douconel;
 use bimap::BiHashMap;
 use itertools::Itertools;
-use petgraph::{Directed, csr::Csr};
+use petgraph::{Directed, Undirected, csr::Csr};
 use slotmap::Key;
 use std::collections::HashSet;
 
 impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
     // Construct a graph with edge filters, vertex filters, and a weight function.
+    // The graph is directed: it has an edge for every half-edge (unless filtered), with its own weight (see `undirected_graph_with_weights` for one edge per pair of twins).
     #[must_use]
     pub fn graph_modified<W, T>(
         &self,
@@ -53,11 +54,38 @@ impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default>
     pub fn graph<W, T>(&self) -> (Csr<(), (), Directed, u32>, BiHashMap<VertID, u32>)
     where
         W: Fn(EdgeID) -> T,
-        T: Clone,
     {
         self.graph_modified(&HashSet::new(), &HashSet::new(), |_| ())
     }
 
+    // Construct an undirected graph with a weight function: an edge for every pair of twins, with the weight of its canonical half-edge (see `canonical`).
+    // The weight function is evaluated once per undirected edge.
+    pub fn undirected_graph_with_weights<W, T>(&self, weight_function: W) -> (Csr<(), T, Undirected, u32>, BiHashMap<VertID, u32>)
+    where
+        W: Fn(EdgeID) -> T,
+        T: Clone,
+    {
+        let mut key_to_int = BiHashMap::new();
+        for (i, id) in self.verts.keys().enumerate() {
+            key_to_int.insert(id, i as u32);
+        }
+
+        // An undirected graph needs its edges in both directions.
+        let sorted_edges = self
+            .undirected_edges()
+            .into_iter()
+            .flat_map(|id| {
+                let (u, v) = self.endpoints(id);
+                let (u, v) = (key_to_int.get_by_left(&u).unwrap().to_owned(), key_to_int.get_by_left(&v).unwrap().to_owned());
+                let weight = weight_function(id);
+                [(u, v, weight.clone()), (v, u, weight)]
+            })
+            .sorted_by_key(|&(u, v, _)| (u, v))
+            .collect::<Vec<_>>();
+
+        (Csr::from_sorted_edges(&sorted_edges).unwrap(), key_to_int)
+    }
+
     // // Iterator of edges into petgraph graph.
     // pub fn graph_from_edges<I, W, T>(&self, edges: I, weight_function: W) -> DiGraphMap<VertID, T>
     // where
@@ -0,0 +1,378 @@
+use crate::douconel::Douconel;
+use bimap::BiHashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned, ser::Error as _};
+use slotmap::{Key, SecondaryMap};
+use std::{any::Any, collections::HashMap, fmt::Debug};
+
+// The (JSON) values of a serialized layer, and the function that serializes a value of a layer.
+type SerializedValues<K> = SecondaryMap<K, serde_json::Value>;
+type SerializeFn<T> = fn(&T) -> Result<serde_json::Value, serde_json::Error>;
+
+// A (type-erased) property layer: a value for every element of one kind (vertices, edges, or faces).
+trait Layer<K: Key>: Send + Sync {
+    fn insert_default(&mut self, key: K);
+    fn remove(&mut self, key: K);
+    fn remap(&mut self, map: &BiHashMap<K, K>);
+    fn inherit(&self, keys: &[K], parents: &HashMap<K, K>) -> Box<dyn Layer<K>>;
+    fn copy_from(&mut self, other: &dyn Layer<K>, parents: &HashMap<K, K>);
+    fn serialize(&self) -> Option<Result<SerializedValues<K>, serde_json::Error>>;
+    fn clone_box(&self) -> Box<dyn Layer<K>>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct TypedLayer<K: Key, T> {
+    map: SecondaryMap<K, T>,
+    // Only set for layers that are included in serialization.
+    serialize: Option<SerializeFn<T>>,
+}
+
+impl<K: Key + 'static, T: Default + Clone + Send + Sync + 'static> Layer<K> for TypedLayer<K, T> {
+    fn insert_default(&mut self, key: K) {
+        self.map.insert(key, T::default());
+    }
+
+    fn remove(&mut self, key: K) {
+        self.map.remove(key);
+    }
+
+    fn remap(&mut self, map: &BiHashMap<K, K>) {
+        self.map = std::mem::take(&mut self.map)
+            .into_iter()
+            .filter_map(|(old_id, value)| map.get_by_left(&old_id).map(|&new_id| (new_id, value)))
+            .collect();
+    }
+
+    fn inherit(&self, keys: &[K], parents: &HashMap<K, K>) -> Box<dyn Layer<K>> {
+        let map = keys
+            .iter()
+            .map(|&key| {
+                let value = parents.get(&key).and_then(|&parent| self.map.get(parent)).cloned();
+                (key, value.unwrap_or_default())
+            })
+            .collect();
+        Box::new(Self {
+            map,
+            serialize: self.serialize,
+        })
+    }
+
+    // Layers of a different type are ignored.
+    fn copy_from(&mut self, other: &dyn Layer<K>, parents: &HashMap<K, K>) {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            for (&key, &parent) in parents {
+                if let Some(value) = other.map.get(parent) {
+                    self.map.insert(key, value.clone());
+                }
+            }
+        }
+    }
+
+    fn serialize(&self) -> Option<Result<SerializedValues<K>, serde_json::Error>> {
+        self.serialize
+            .map(|serialize| self.map.iter().map(|(key, value)| serialize(value).map(|value| (key, value))).collect())
+    }
+
+    fn clone_box(&self) -> Box<dyn Layer<K>> {
+        Box::new(Self {
+            map: self.map.clone(),
+            serialize: self.serialize,
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Named, typed property layers for one kind of element (vertices, edges, or faces).
+// Every layer holds a value for every element; new elements get the default value, and removed elements are removed from all layers.
+// Only layers added with `add_serialized` are included in serialization. After deserialization, their data is restored once they are added again (with `add_serialized`).
+// Until then, their (serialized) values are kept per element, and are removed and remapped along with the elements.
+pub struct Properties<K: Key> {
+    layers: HashMap<String, Box<dyn Layer<K>>>,
+    pending: HashMap<String, SerializedValues<K>>,
+}
+
+impl<K: Key> Default for Properties<K> {
+    fn default() -> Self {
+        Self {
+            layers: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Key> Clone for Properties<K> {
+    fn clone(&self) -> Self {
+        Self {
+            layers: self.layers.iter().map(|(name, layer)| (name.clone(), layer.clone_box())).collect(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<K: Key> Debug for Properties<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Properties")
+            .field("layers", &self.layers.keys().collect::<Vec<_>>())
+            .field("pending", &self.pending.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+// Every layer is serialized as a list of (element, value) pairs. Fails if a value of a layer cannot be serialized.
+impl<K: Key + Serialize> Serialize for Properties<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut layers = self.pending.clone();
+        for (name, layer) in &self.layers {
+            if let Some(values) = layer.serialize() {
+                let values = values.map_err(|err| S::Error::custom(format!("Property layer {name} cannot be serialized ({err})")))?;
+                layers.insert(name.clone(), values);
+            }
+        }
+        layers
+            .into_iter()
+            .map(|(name, values)| (name, values.into_iter().collect::<Vec<_>>()))
+            .collect::<HashMap<_, _>>()
+            .serialize(serializer)
+    }
+}
+
+impl<'de, K: Key + Deserialize<'de>> Deserialize<'de> for Properties<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let layers = HashMap::<String, Vec<(K, serde_json::Value)>>::deserialize(deserializer)?;
+        Ok(Self {
+            layers: HashMap::new(),
+            pending: layers.into_iter().map(|(name, values)| (name, values.into_iter().collect())).collect(),
+        })
+    }
+}
+
+impl<K: Key + 'static> Properties<K> {
+    // Adds a layer with the given name (replacing any existing layer with this name), with the default value for every given element.
+    pub fn add<T: Default + Clone + Send + Sync + 'static>(&mut self, name: &str, keys: impl Iterator<Item = K>) -> &mut SecondaryMap<K, T> {
+        self.insert(name, keys.map(|key| (key, T::default())).collect(), None)
+    }
+
+    // Adds a layer with the given name that is included in serialization (see `add`).
+    // If the layer was deserialized (and not yet added), its data is restored.
+    // Returns an error (without adding the layer) if the deserialized data cannot be restored as values of type `T`.
+    pub fn add_serialized<T: Default + Clone + Send + Sync + Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &str,
+        keys: impl Iterator<Item = K>,
+    ) -> Result<&mut SecondaryMap<K, T>, serde_json::Error> {
+        let map = keys
+            .map(|key| {
+                let value = self.pending.get(name).and_then(|restored| restored.get(key));
+                value
+                    .map_or_else(|| Ok(T::default()), |value| serde_json::from_value(value.clone()))
+                    .map(|value| (key, value))
+            })
+            .collect::<Result<_, _>>()?;
+        self.pending.remove(name);
+        Ok(self.insert(name, map, Some(|value: &T| serde_json::to_value(value))))
+    }
+
+    fn insert<T: Default + Clone + Send + Sync + 'static>(
+        &mut self,
+        name: &str,
+        map: SecondaryMap<K, T>,
+        serialize: Option<SerializeFn<T>>,
+    ) -> &mut SecondaryMap<K, T> {
+        self.layers.insert(name.to_owned(), Box::new(TypedLayer { map, serialize }));
+        self.get_mut(name).unwrap()
+    }
+
+    // Returns the layer with the given name. Returns None if there is no such layer (or if it has a different type).
+    #[must_use]
+    pub fn get<T: 'static>(&self, name: &str) -> Option<&SecondaryMap<K, T>> {
+        self.layers
+            .get(name)
+            .and_then(|layer| layer.as_any().downcast_ref::<TypedLayer<K, T>>())
+            .map(|layer| &layer.map)
+    }
+
+    // Returns the layer with the given name (mutable). Returns None if there is no such layer (or if it has a different type).
+    pub fn get_mut<T: 'static>(&mut self, name: &str) -> Option<&mut SecondaryMap<K, T>> {
+        self.layers
+            .get_mut(name)
+            .and_then(|layer| layer.as_any_mut().downcast_mut::<TypedLayer<K, T>>())
+            .map(|layer| &mut layer.map)
+    }
+
+    // Removes the layer with the given name. Returns whether there was such a layer.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.layers.remove(name).is_some() || self.pending.remove(name).is_some()
+    }
+}
+
+impl<K: Key> Properties<K> {
+    // Returns the names of all layers.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys().map(String::as_str)
+    }
+
+    pub(crate) fn insert_key(&mut self, key: K) {
+        for layer in self.layers.values_mut() {
+            layer.insert_default(key);
+        }
+    }
+
+    pub(crate) fn remove_key(&mut self, key: K) {
+        for layer in self.layers.values_mut() {
+            layer.remove(key);
+        }
+        for values in self.pending.values_mut() {
+            values.remove(key);
+        }
+    }
+
+    pub(crate) fn remap(&mut self, map: &BiHashMap<K, K>) {
+        for layer in self.layers.values_mut() {
+            layer.remap(map);
+        }
+        for values in self.pending.values_mut() {
+            *values = std::mem::take(values)
+                .into_iter()
+                .filter_map(|(old_id, value)| map.get_by_left(&old_id).map(|&new_id| (new_id, value)))
+                .collect();
+        }
+    }
+
+    // Copies the values of another set of layers, for the elements that are added from it (see `Douconel::append`).
+    // `keys` are all elements (existing and added), and `map` maps the elements of the other layers (left) to the added elements (right).
+    // Layers that only exist in the other set are added (with the default value for the existing elements).
+    pub(crate) fn append(&mut self, other: &Self, keys: &[K], map: &BiHashMap<K, K>) {
+        let parents = map.iter().map(|(&other_key, &key)| (key, other_key)).collect::<HashMap<_, _>>();
+        for (name, other_layer) in &other.layers {
+            match self.layers.get_mut(name) {
+                Some(layer) => layer.copy_from(other_layer.as_ref(), &parents),
+                None => {
+                    self.layers.insert(name.clone(), other_layer.inherit(keys, &parents));
+                }
+            }
+        }
+        for (name, other_values) in &other.pending {
+            let values = self.pending.entry(name.clone()).or_default();
+            for (&key, &other_key) in &parents {
+                if let Some(value) = other_values.get(other_key) {
+                    values.insert(key, value.clone());
+                }
+            }
+        }
+    }
+
+    // Returns the layers for a new set of elements (e.g., of a subdivided mesh), where every element with a parent (in `parents`) gets the value of its parent,
+    // and every other element gets the default value.
+    pub(crate) fn inherit(&self, keys: &[K], parents: &HashMap<K, K>) -> Self {
+        Self {
+            layers: self.layers.iter().map(|(name, layer)| (name.clone(), layer.inherit(keys, parents))).collect(),
+            pending: self
+                .pending
+                .iter()
+                .map(|(name, values)| {
+                    let inherited = keys
+                        .iter()
+                        .filter_map(|&key| parents.get(&key).and_then(|&parent| values.get(parent)).map(|value| (key, value.clone())))
+                        .collect();
+                    (name.clone(), inherited)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<VertID: Key + 'static, V: Default, EdgeID: Key + 'static, E: Default, FaceID: Key + 'static, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Adds a named per-vertex property (replacing any existing property with this name), with the default value for every vertex.
+    pub fn add_vertex_property<T: Default + Clone + Send + Sync + 'static>(&mut self, name: &str) -> &mut SecondaryMap<VertID, T> {
+        let keys = self.verts.keys().collect::<Vec<_>>();
+        self.vert_props_mut().add(name, keys.into_iter())
+    }
+
+    // Adds a named per-vertex property that is included in serialization (restoring its data if it was deserialized).
+    // Returns an error if the deserialized data cannot be restored as values of type `T`.
+    pub fn add_vertex_property_serialized<T: Default + Clone + Send + Sync + Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut SecondaryMap<VertID, T>, serde_json::Error> {
+        let keys = self.verts.keys().collect::<Vec<_>>();
+        self.vert_props_mut().add_serialized(name, keys.into_iter())
+    }
+
+    #[must_use]
+    pub fn vertex_property<T: 'static>(&self, name: &str) -> Option<&SecondaryMap<VertID, T>> {
+        self.vert_props.get(name)
+    }
+
+    pub fn vertex_property_mut<T: 'static>(&mut self, name: &str) -> Option<&mut SecondaryMap<VertID, T>> {
+        self.vert_props_mut().get_mut(name)
+    }
+
+    pub fn remove_vertex_property(&mut self, name: &str) -> bool {
+        self.vert_props_mut().remove(name)
+    }
+
+    // Adds a named per-edge property (replacing any existing property with this name), with the default value for every edge.
+    pub fn add_edge_property<T: Default + Clone + Send + Sync + 'static>(&mut self, name: &str) -> &mut SecondaryMap<EdgeID, T> {
+        let keys = self.edges.keys().collect::<Vec<_>>();
+        self.edge_props_mut().add(name, keys.into_iter())
+    }
+
+    // Adds a named per-edge property that is included in serialization (restoring its data if it was deserialized).
+    // Returns an error if the deserialized data cannot be restored as values of type `T`.
+    pub fn add_edge_property_serialized<T: Default + Clone + Send + Sync + Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut SecondaryMap<EdgeID, T>, serde_json::Error> {
+        let keys = self.edges.keys().collect::<Vec<_>>();
+        self.edge_props_mut().add_serialized(name, keys.into_iter())
+    }
+
+    #[must_use]
+    pub fn edge_property<T: 'static>(&self, name: &str) -> Option<&SecondaryMap<EdgeID, T>> {
+        self.edge_props.get(name)
+    }
+
+    pub fn edge_property_mut<T: 'static>(&mut self, name: &str) -> Option<&mut SecondaryMap<EdgeID, T>> {
+        self.edge_props_mut().get_mut(name)
+    }
+
+    pub fn remove_edge_property(&mut self, name: &str) -> bool {
+        self.edge_props_mut().remove(name)
+    }
+
+    // Adds a named per-face property (replacing any existing property with this name), with the default value for every face.
+    pub fn add_face_property<T: Default + Clone + Send + Sync + 'static>(&mut self, name: &str) -> &mut SecondaryMap<FaceID, T> {
+        let keys = self.faces.keys().collect::<Vec<_>>();
+        self.face_props_mut().add(name, keys.into_iter())
+    }
+
+    // Adds a named per-face property that is included in serialization (restoring its data if it was deserialized).
+    // Returns an error if the deserialized data cannot be restored as values of type `T`.
+    pub fn add_face_property_serialized<T: Default + Clone + Send + Sync + Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut SecondaryMap<FaceID, T>, serde_json::Error> {
+        let keys = self.faces.keys().collect::<Vec<_>>();
+        self.face_props_mut().add_serialized(name, keys.into_iter())
+    }
+
+    #[must_use]
+    pub fn face_property<T: 'static>(&self, name: &str) -> Option<&SecondaryMap<FaceID, T>> {
+        self.face_props.get(name)
+    }
+
+    pub fn face_property_mut<T: 'static>(&mut self, name: &str) -> Option<&mut SecondaryMap<FaceID, T>> {
+        self.face_props_mut().get_mut(name)
+    }
+
+    pub fn remove_face_property(&mut self, name: &str) -> bool {
+        self.face_props_mut().remove(name)
+    }
+}
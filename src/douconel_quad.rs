@@ -0,0 +1,200 @@
+use crate::douconel::{ConstructionOptions, Douconel, MeshError};
+use bimap::BiHashMap;
+use itertools::Itertools;
+use slotmap::Key;
+use std::collections::{HashMap, HashSet};
+
+impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Returns an error with the first face that is not a quad, if any.
+    fn check_quad(&self) -> Result<(), MeshError<VertID, EdgeID, FaceID>> {
+        self.faces
+            .keys()
+            .find(|&face_id| !self.is_quad(face_id))
+            .map_or(Ok(()), |face_id| Err(MeshError::NotQuad(face_id)))
+    }
+
+    // Returns the singularities of a quad mesh: the interior vertices with valence other than 4.
+    // Returns an error if the mesh is not a quad mesh.
+    pub fn singularities(&self) -> Result<Vec<VertID>, MeshError<VertID, EdgeID, FaceID>> {
+        self.check_quad()?;
+        Ok(self.find_singularities())
+    }
+
+    fn find_singularities(&self) -> Vec<VertID> {
+        self.verts
+            .keys()
+            .filter(|&vert_id| !self.is_boundary_vertex(vert_id) && self.is_irregular(vert_id))
+            .collect()
+    }
+
+    // Returns whether a vertex is irregular: an interior vertex with valence other than 4, or a boundary vertex with valence other than 3 (a corner of the boundary).
+    fn is_irregular(&self, vert_id: VertID) -> bool {
+        if self.is_boundary_vertex(vert_id) {
+            self.valence(vert_id) != 3
+        } else {
+            self.valence(vert_id) != 4
+        }
+    }
+
+    // Returns the separatrices of a quad mesh: the edge loops traced from every irregular vertex (the singularities and the corners of the boundary),
+    // along each of its outgoing interior edges, until they reach another irregular vertex (or the boundary). See `edge_loop`.
+    // Every separatrix starts at an irregular vertex, and its edges are ordered head to tail. A separatrix between two irregular vertices is returned once.
+    // Returns an error if the mesh is not a quad mesh.
+    pub fn separatrices(&self) -> Result<Vec<Vec<EdgeID>>, MeshError<VertID, EdgeID, FaceID>> {
+        self.check_quad()?;
+        Ok(self.trace_separatrices())
+    }
+
+    fn trace_separatrices(&self) -> Vec<Vec<EdgeID>> {
+        let mut traced = HashSet::new();
+        let mut separatrices = vec![];
+        for vert_id in self.verts.keys().filter(|&vert_id| self.is_irregular(vert_id)) {
+            for edge_id in self.outgoing_iter(vert_id) {
+                // Boundary edges are borders of the patches already.
+                if traced.contains(&self.canonical(edge_id)) || self.is_boundary(edge_id) || self.is_boundary(self.twin(edge_id)) {
+                    continue;
+                }
+                // The vertex is irregular, so the loop does not extend backwards beyond it.
+                let separatrix = self.edge_loop(edge_id);
+                traced.extend(separatrix.iter().map(|&separatrix_edge_id| self.canonical(separatrix_edge_id)));
+                separatrices.push(separatrix);
+            }
+        }
+        separatrices
+    }
+
+    // Returns the patches of a quad mesh: the connected sets of faces that are separated by the separatrices.
+    // Returns an error if the mesh is not a quad mesh.
+    pub fn patches(&self) -> Result<Vec<HashSet<FaceID>>, MeshError<VertID, EdgeID, FaceID>> {
+        self.check_quad()?;
+        Ok(self.patches_with(&self.patch_borders()))
+    }
+
+    // Returns the borders between patches (as canonical half-edges): the edges of the separatrices and the boundary edges.
+    fn patch_borders(&self) -> HashSet<EdgeID> {
+        self.trace_separatrices()
+            .into_iter()
+            .flatten()
+            .chain(self.edges.keys().filter(|&edge_id| self.is_boundary(edge_id)))
+            .map(|edge_id| self.canonical(edge_id))
+            .collect()
+    }
+
+    // Returns the connected sets of faces that are separated by the given borders (as canonical half-edges).
+    fn patches_with(&self, borders: &HashSet<EdgeID>) -> Vec<HashSet<FaceID>> {
+        hutspot::graph::find_ccs(&self.face_ids(), |face_id| {
+            self.edges_iter(face_id)
+                .filter(|&edge_id| !borders.contains(&self.canonical(edge_id)))
+                .filter_map(|edge_id| self.edge_face.get(self.twin(edge_id)).copied())
+                .collect()
+        })
+    }
+}
+
+impl<VertID: Key, V: Default + Clone, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Returns the patch layout of a quad mesh: a coarse mesh with a face per patch (see `patches`), and a vertex per patch corner.
+    // The patch corners are the irregular vertices (the singularities and the corners of the boundary), and the vertices where separatrices meet (or end on the boundary).
+    // The coarse vertices get the data of their (fine) corner vertex.
+    // Returns the coarse mesh, a map from the (fine) corner vertices to the coarse vertices, and a map from every (fine) face to its patch (coarse face).
+    // Returns an error if the mesh is not a quad mesh, or if a patch is not a disk with at least three corners (e.g., on a torus without singularities).
+    // Every side of a patch (between two consecutive corners) becomes a single edge of the layout, so it returns an error if two different sides connect the same two corners
+    // (e.g., on a cylinder that is cut by two separatrices, where a side of the boundary ends at the same corners as a separatrix).
+    pub fn patch_layout(&self) -> Result<(Self, BiHashMap<VertID, VertID>, HashMap<FaceID, FaceID>), MeshError<VertID, EdgeID, FaceID>> {
+        self.check_quad()?;
+        let borders = self.patch_borders();
+        let is_border = |edge_id: EdgeID| borders.contains(&self.canonical(edge_id));
+
+        let corners = self
+            .verts
+            .keys()
+            .filter(|&vert_id| {
+                let nr_borders = self.outgoing_iter(vert_id).filter(|&edge_id| is_border(edge_id)).count();
+                self.is_irregular(vert_id) || (nr_borders != 0 && nr_borders != 2)
+            })
+            .collect_vec();
+        let corner_to_index = corners.iter().enumerate().map(|(index, &vert_id)| (vert_id, index)).collect::<HashMap<_, _>>();
+
+        let patches = self.patches_with(&borders);
+        let mut layout_faces = vec![];
+        // The sides of the patches by their corners (as unordered pair): a side (as its smallest canonical half-edge), and the patch it was found in.
+        let mut sides = HashMap::new();
+        for (patch_index, patch) in patches.iter().enumerate() {
+            let patch_border = patch
+                .iter()
+                .flat_map(|&face_id| self.edges_iter(face_id))
+                .filter(|&edge_id| is_border(edge_id))
+                .collect::<HashSet<_>>();
+            // A patch without a border is a closed surface (not a disk).
+            let Some(&start) = patch_border.iter().min() else {
+                return Err(MeshError::PatchNotDisk(patch_index));
+            };
+
+            // Walk along the border of the patch: from the end of a border edge, rotate (inside the patch) to the next border edge.
+            let mut cycle = vec![start];
+            let mut cur = start;
+            loop {
+                let mut next = self.next(cur);
+                while !is_border(next) {
+                    next = self.next(self.twin(next));
+                }
+                if next == start || cycle.len() > patch_border.len() {
+                    break;
+                }
+                cycle.push(next);
+                cur = next;
+            }
+            if cycle.len() != patch_border.len() {
+                return Err(MeshError::PatchNotDisk(patch_index));
+            }
+
+            let corner_positions = cycle
+                .iter()
+                .positions(|&edge_id| corner_to_index.contains_key(&self.root(edge_id)))
+                .collect_vec();
+            if corner_positions.len() < 3 {
+                return Err(MeshError::PatchTooFewCorners(patch_index));
+            }
+            for (&from, &to) in corner_positions.iter().circular_tuple_windows() {
+                let length = (to + cycle.len() - from) % cycle.len();
+                let side = (from..from + length).map(|position| self.canonical(cycle[position % cycle.len()])).min();
+                let (a, b) = (corner_to_index[&self.root(cycle[from])], corner_to_index[&self.root(cycle[to])]);
+                if let Some((other_side, other_patch_index)) = sides.insert((a.min(b), a.max(b)), (side, patch_index))
+                    && other_side != side
+                {
+                    return Err(MeshError::PatchSidesShareCorners(other_patch_index, patch_index));
+                }
+            }
+
+            let patch_corners = corner_positions
+                .into_iter()
+                .map(|position| corner_to_index[&self.root(cycle[position])])
+                .collect_vec();
+            layout_faces.push(patch_corners);
+        }
+
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            ..ConstructionOptions::default()
+        };
+        let (mut layout, layout_vert_map, layout_face_map, _) = Self::from_faces_with(&layout_faces, options)?;
+
+        let mut vert_map = BiHashMap::new();
+        for (index, &vert_id) in corners.iter().enumerate() {
+            if let Some(&layout_vert_id) = layout_vert_map.get_by_left(&index) {
+                layout.verts[layout_vert_id] = self.verts[vert_id].clone();
+                vert_map.insert(vert_id, layout_vert_id);
+            }
+        }
+
+        let face_to_patch = patches
+            .into_iter()
+            .enumerate()
+            .flat_map(|(patch_index, patch)| {
+                let layout_face_id = layout_face_map.get_by_left(&patch_index).copied().unwrap();
+                patch.into_iter().map(move |face_id| (face_id, layout_face_id))
+            })
+            .collect();
+
+        Ok((layout, vert_map, face_to_patch))
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    douconel::Douconel,
+    douconel_embedded::{Float, HasPosition},
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use slotmap::{Key, SecondaryMap};
+use std::collections::HashSet;
+
+// The invariants (and properties) of a DCEL that can be violated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Invariant {
+    // Edge has no root.
+    MissingRoot,
+    // Edge has no next.
+    MissingNext,
+    // Edge has no twin.
+    MissingTwin,
+    // Edge and its twin have no face (only one of them may be a boundary edge).
+    MissingFace,
+    // Vertex has no representative edge.
+    MissingVrep,
+    // Face has no representative edge.
+    MissingFrep,
+    // Edge refers to a non-existing root.
+    InvalidRoot,
+    // Edge refers to a non-existing face.
+    InvalidFace,
+    // Edge refers to a non-existing next.
+    InvalidNext,
+    // Edge refers to a non-existing twin.
+    InvalidTwin,
+    // Vertex refers to a non-existing representative edge.
+    InvalidVrep,
+    // Face refers to a non-existing representative edge.
+    InvalidFrep,
+    // this->twin->twin == this
+    TwinOfTwin,
+    // this->twin->next->root == this->root
+    TwinNextRoot,
+    // this->next->face == this->face
+    NextFace,
+    // this->next->...->next == this
+    NextCycle,
+    // this->vrep->root == this
+    VrepRoot,
+    // this->frep->face == this
+    FrepFace,
+    // The edges around the vertex do not form a single fan (or the vertex has more than one boundary).
+    NonManifoldVertex,
+    // The mesh has more than one connected component.
+    NotConnected,
+    // The face has less than three distinct corners, or (close to) zero area.
+    DegenerateFace,
+}
+
+// The element of the mesh that violates an invariant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Element<VertID, EdgeID, FaceID> {
+    Vert(VertID),
+    Edge(EdgeID),
+    Face(FaceID),
+    Mesh,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Violation<VertID, EdgeID, FaceID> {
+    pub element: Element<VertID, EdgeID, FaceID>,
+    pub invariant: Invariant,
+}
+
+// All violations found by validating a mesh.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationReport<VertID, EdgeID, FaceID> {
+    pub violations: Vec<Violation<VertID, EdgeID, FaceID>>,
+}
+
+impl<VertID: Key, EdgeID: Key, FaceID: Key> ValidationReport<VertID, EdgeID, FaceID> {
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    // Returns the violations of the given invariant.
+    #[must_use]
+    pub fn of(&self, invariant: Invariant) -> Vec<Element<VertID, EdgeID, FaceID>> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.invariant == invariant)
+            .map(|violation| violation.element)
+            .collect()
+    }
+
+    fn push(&mut self, element: Element<VertID, EdgeID, FaceID>, invariant: Invariant) {
+        self.violations.push(Violation { element, invariant });
+    }
+}
+
+// The optional checks of a validation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidationOptions {
+    // Check that the edges around every vertex form a single fan.
+    pub manifold: bool,
+    // Check that the mesh has a single connected component.
+    pub connected: bool,
+}
+
+impl<VertID: Key, V: Default, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Validates the DCEL structure (properties, references, and invariants), see `validate_with`.
+    #[must_use]
+    pub fn validate(&self) -> ValidationReport<VertID, EdgeID, FaceID> {
+        self.validate_with(ValidationOptions::default())
+    }
+
+    // Validates the DCEL structure (properties, references, and invariants), and optionally manifoldness and connectivity.
+    // Unlike the `assert_*` functions, this never panics, and reports every violation (by element and invariant).
+    // Manifoldness and connectivity are only checked if the DCEL structure itself is valid.
+    #[must_use]
+    pub fn validate_with(&self, options: ValidationOptions) -> ValidationReport<VertID, EdgeID, FaceID> {
+        let mut report = ValidationReport { violations: vec![] };
+
+        // Properties and references
+        for edge_id in self.edges.keys() {
+            let element = Element::Edge(edge_id);
+            match self.edge_root.get(edge_id) {
+                None => report.push(element, Invariant::MissingRoot),
+                Some(&root_id) if !self.verts.contains_key(root_id) => report.push(element, Invariant::InvalidRoot),
+                Some(_) => {}
+            }
+            match self.edge_next.get(edge_id) {
+                None => report.push(element, Invariant::MissingNext),
+                Some(&next_id) if !self.edges.contains_key(next_id) => report.push(element, Invariant::InvalidNext),
+                Some(_) => {}
+            }
+            match self.edge_twin.get(edge_id) {
+                None => report.push(element, Invariant::MissingTwin),
+                Some(&twin_id) if !self.edges.contains_key(twin_id) => report.push(element, Invariant::InvalidTwin),
+                Some(&twin_id) if !self.edge_face.contains_key(edge_id) && !self.edge_face.contains_key(twin_id) => {
+                    report.push(element, Invariant::MissingFace);
+                }
+                Some(_) => {}
+            }
+            if let Some(&face_id) = self.edge_face.get(edge_id)
+                && !self.faces.contains_key(face_id)
+            {
+                report.push(element, Invariant::InvalidFace);
+            }
+        }
+        for vert_id in self.verts.keys() {
+            match self.vert_rep.get(vert_id) {
+                None => report.push(Element::Vert(vert_id), Invariant::MissingVrep),
+                Some(&rep_id) if !self.edges.contains_key(rep_id) => report.push(Element::Vert(vert_id), Invariant::InvalidVrep),
+                Some(_) => {}
+            }
+        }
+        for face_id in self.faces.keys() {
+            match self.face_rep.get(face_id) {
+                None => report.push(Element::Face(face_id), Invariant::MissingFrep),
+                Some(&rep_id) if !self.edges.contains_key(rep_id) => report.push(Element::Face(face_id), Invariant::InvalidFrep),
+                Some(_) => {}
+            }
+        }
+
+        // Invariants (only of elements with valid references)
+        let root = |id| self.edge_root.get(id).copied();
+        let next = |id| self.edge_next.get(id).copied().filter(|&next_id| self.edges.contains_key(next_id));
+        let twin = |id| self.edge_twin.get(id).copied().filter(|&twin_id| self.edges.contains_key(twin_id));
+
+        let off_cycle = self.off_next_cycle();
+
+        for edge_id in self.edges.keys() {
+            let element = Element::Edge(edge_id);
+            if let Some(twin_id) = twin(edge_id) {
+                if twin(twin_id) != Some(edge_id) {
+                    report.push(element, Invariant::TwinOfTwin);
+                }
+                if let Some(next_id) = next(twin_id)
+                    && root(next_id) != root(edge_id)
+                {
+                    report.push(element, Invariant::TwinNextRoot);
+                }
+            }
+            if let Some(next_id) = next(edge_id)
+                && self.edge_face.get(next_id) != self.edge_face.get(edge_id)
+            {
+                report.push(element, Invariant::NextFace);
+            }
+            if off_cycle.contains(&edge_id) {
+                report.push(element, Invariant::NextCycle);
+            }
+        }
+        for (vert_id, &rep_id) in &self.vert_rep {
+            if self.verts.contains_key(vert_id) && self.edges.contains_key(rep_id) && root(rep_id) != Some(vert_id) {
+                report.push(Element::Vert(vert_id), Invariant::VrepRoot);
+            }
+        }
+        for (face_id, &rep_id) in &self.face_rep {
+            if self.faces.contains_key(face_id) && self.edges.contains_key(rep_id) && self.edge_face.get(rep_id) != Some(&face_id) {
+                report.push(Element::Face(face_id), Invariant::FrepFace);
+            }
+        }
+
+        if !report.is_valid() {
+            return report;
+        }
+
+        // Manifoldness: the edges around every vertex form a single fan, with at most one boundary.
+        if options.manifold {
+            let edges_per_root = self.edges.keys().counts_by(|edge_id| self.root(edge_id));
+            for vert_id in self.verts.keys() {
+                let outgoing = self.outgoing(vert_id);
+                let boundaries = outgoing.iter().filter(|&&edge_id| self.is_boundary(edge_id)).count();
+                if edges_per_root.get(&vert_id).copied().unwrap_or_default() != outgoing.len() || boundaries > 1 {
+                    report.push(Element::Vert(vert_id), Invariant::NonManifoldVertex);
+                }
+            }
+        }
+
+        // Connectivity
+        if options.connected && !self.verts.is_empty() && !self.is_connected() {
+            report.push(Element::Mesh, Invariant::NotConnected);
+        }
+
+        report
+    }
+
+    // Returns the edges that do not return to themselves by following next (with valid references). Every edge is walked once (in linear time):
+    // a walk stops when it breaks, when it closes a cycle (only the edges on that cycle are on a cycle), or when it reaches an edge of an earlier walk.
+    fn off_next_cycle(&self) -> HashSet<EdgeID> {
+        let next = |id| self.edge_next.get(id).copied().filter(|&next_id| self.edges.contains_key(next_id));
+        let mut off_cycle = HashSet::<EdgeID>::new();
+        let mut walked = SecondaryMap::new();
+        for (walk, start_id) in self.edges.keys().enumerate() {
+            let mut path = vec![];
+            let mut cur_id = Some(start_id);
+            while let Some(edge_id) = cur_id.filter(|&edge_id| !walked.contains_key(edge_id)) {
+                walked.insert(edge_id, (walk, path.len()));
+                path.push(edge_id);
+                cur_id = next(edge_id);
+            }
+            let on_cycle_from = match cur_id.and_then(|edge_id| walked.get(edge_id)) {
+                Some(&(edge_walk, index)) if edge_walk == walk => index,
+                _ => path.len(),
+            };
+            off_cycle.extend(&path[..on_cycle_from]);
+        }
+        off_cycle
+    }
+}
+
+impl<VertID: Key, V: Default + HasPosition, EdgeID: Key, E: Default, FaceID: Key, F: Default> Douconel<VertID, V, EdgeID, E, FaceID, F> {
+    // Validates the DCEL structure (see `validate_with`), and the embedding: faces may not be degenerate.
+    // A face is degenerate if it has less than three distinct corners (positions), or an area smaller than `epsilon`.
+    #[must_use]
+    pub fn validate_embedded(&self, options: ValidationOptions, epsilon: Float) -> ValidationReport<VertID, EdgeID, FaceID> {
+        let mut report = self.validate_with(options);
+        if !report.is_valid() {
+            return report;
+        }
+
+        for face_id in self.faces.keys() {
+            let positions = self.corners(face_id).into_iter().map(|vert_id| self.position(vert_id)).collect_vec();
+            let distinct = positions.iter().enumerate().filter(|&(i, p)| positions[..i].iter().all(|q| q != p)).count();
+            if distinct < 3 || self.area(face_id) < epsilon {
+                report.push(Element::Face(face_id), Invariant::DegenerateFace);
+            }
+        }
+
+        report
+    }
+}
@@ -3,17 +3,29 @@
 pub mod douconel;
 pub mod douconel_bevy;
 pub mod douconel_embedded;
+pub mod douconel_history;
 pub mod douconel_io;
+pub mod douconel_iter;
 pub mod douconel_petgraph;
+pub mod douconel_properties;
+pub mod douconel_quad;
+pub mod douconel_validation;
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{
+        collections::{HashMap, HashSet},
+        path::PathBuf,
+    };
 
     use crate::{
-        douconel::{Douconel, Empty},
-        douconel_embedded::EmbeddedVertex,
+        douconel::{AttributePolicy, ConstructionOptions, Douconel, Empty, MeshError, UndirectedEdgeMap},
+        douconel_embedded::{EmbeddedVertex, HasPosition, InterpolatePosition},
+        douconel_validation::{Element, Invariant, ValidationOptions, Violation},
     };
+    use hutspot::geom::Vector3D;
+    use itertools::Itertools;
+    use rand::{SeedableRng, rngs::StdRng};
 
     slotmap::new_key_type! {
         struct VertID;
@@ -37,6 +49,722 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_manual_open() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _)) = douconel {
+            assert!(douconel.nr_verts() == 4);
+            assert!(douconel.nr_edges() == 6 * 2);
+            assert!(douconel.nr_faces() == 3);
+
+            let boundary_loops = douconel.boundary_loops();
+            assert!(boundary_loops.len() == 1);
+            assert!(boundary_loops[0].len() == 3);
+
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let v1 = vert_map.get_by_left(&1).copied().unwrap();
+            assert!(douconel.is_boundary_vertex(v0));
+            assert!(!douconel.is_boundary_vertex(v1));
+            assert!(douconel.vneighbors(v0).len() == 3);
+            assert!(douconel.star(v0).len() == 2);
+            assert!(douconel.star(v1).len() == 3);
+        }
+    }
+
+    #[test]
+    fn from_manual_misoriented() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 2, 3]];
+        let oriented = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::orient_faces(&faces);
+        assert!(oriented.is_ok(), "{oriented:?}");
+        if let Ok((_, flipped)) = oriented {
+            assert!(flipped == vec![3]);
+        }
+
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces_with(&faces, ConstructionOptions::default());
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _, report)) = douconel {
+            assert!(douconel.nr_edges() == 6 * 2);
+            assert!(douconel.boundary_loops().is_empty());
+            assert!(report.flipped_faces == vec![3]);
+        }
+    }
+
+    #[test]
+    fn from_manual_mobius() {
+        let faces = vec![vec![0, 1, 4, 3], vec![1, 2, 5, 4], vec![2, 3, 0, 5]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(matches!(douconel, Err(MeshError::NotOrientable)), "{douconel:?}");
+    }
+
+    #[test]
+    fn from_manual_disconnected() {
+        let faces = vec![
+            vec![0, 2, 1],
+            vec![0, 1, 3],
+            vec![1, 2, 3],
+            vec![0, 3, 2],
+            vec![4, 6, 5],
+            vec![4, 5, 7],
+            vec![5, 6, 7],
+        ];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(matches!(douconel, Err(MeshError::NotConnected)));
+
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            ..ConstructionOptions::default()
+        };
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces_with(&faces, options);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _, _)) = douconel {
+            assert!(douconel.nr_verts() == 8);
+            assert!(douconel.nr_edges() == (6 + 6) * 2);
+            assert!(douconel.nr_faces() == 7);
+            assert!(!douconel.is_connected());
+
+            let components = douconel.components();
+            assert!(components.len() == 2);
+            assert!(components.iter().all(|(verts, edges, _)| verts.len() == 4 && edges.len() == 6 * 2));
+            assert!(components.iter().map(|(_, _, faces)| faces.len()).sorted().collect_vec() == vec![3, 4]);
+
+            let split = douconel.split_components();
+            assert!(split.is_ok(), "{split:?}");
+            if let Ok(split) = split {
+                assert!(split.len() == 2);
+                for (component, component_vert_map, component_edge_map, component_face_map) in &split {
+                    assert!(component.is_connected());
+                    assert!(component_vert_map.len() == component.nr_verts());
+                    assert!(component_edge_map.len() == component.nr_edges());
+                    assert!(component_face_map.len() == component.nr_faces());
+                    for edge_id in component.edge_ids() {
+                        let (u, v) = component.endpoints(edge_id);
+                        let original_edge_id = component_edge_map.get_by_left(&edge_id).copied().unwrap();
+                        assert!(
+                            douconel.endpoints(original_edge_id)
+                                == (
+                                    component_vert_map.get_by_left(&u).copied().unwrap(),
+                                    component_vert_map.get_by_left(&v).copied().unwrap()
+                                )
+                        );
+                    }
+                }
+
+                let v4 = vert_map.get_by_left(&4).copied().unwrap();
+                let (open, _, _, _) = split
+                    .iter()
+                    .find(|(_, component_vert_map, _, _)| component_vert_map.contains_right(&v4))
+                    .unwrap();
+                assert!(open.nr_faces() == 3);
+                assert!(open.boundary_loops().len() == 1);
+            }
+        }
+    }
+
+    #[test]
+    fn from_manual_pinched() {
+        // Two tetrahedra touching at vertex 0.
+        let faces = vec![
+            vec![0, 2, 1],
+            vec![0, 1, 3],
+            vec![1, 2, 3],
+            vec![0, 3, 2],
+            vec![0, 5, 4],
+            vec![0, 4, 6],
+            vec![4, 5, 6],
+            vec![0, 6, 5],
+        ];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(matches!(douconel, Err(MeshError::NonManifoldVertex(..))), "{douconel:?}");
+
+        let (split_faces, origin) = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::split_non_manifold_vertices(&faces);
+        assert!(origin == vec![0, 1, 2, 3, 4, 5, 6, 0]);
+        assert!(split_faces[..4] == faces[..4]);
+        assert!(split_faces[4] == vec![7, 5, 4]);
+
+        let options = ConstructionOptions {
+            allow_disconnected: true,
+            split_non_manifold_vertices: true,
+        };
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces_with(&faces, options);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _, report)) = douconel {
+            assert!(report.vertex_origin == origin);
+            assert!(douconel.nr_verts() == 8);
+            assert!(douconel.nr_edges() == (6 + 6) * 2);
+            assert!(douconel.components().len() == 2);
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let v7 = vert_map.get_by_left(&7).copied().unwrap();
+            assert!(douconel.star(v0).len() == 3 && douconel.star(v7).len() == 3);
+        }
+
+        // Two triangles touching at vertex 0 (a bowtie).
+        let faces = vec![vec![0, 1, 2], vec![0, 3, 4]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(matches!(douconel, Err(MeshError::NonManifoldVertex(..))), "{douconel:?}");
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces_with(&faces, options);
+        assert!(douconel.is_ok_and(|(douconel, _, _, _)| douconel.nr_verts() == 6 && douconel.boundary_loops().len() == 2));
+    }
+
+    #[test]
+    fn topology() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.euler_characteristic() == 2);
+            assert!(douconel.genus() == 0);
+            assert!(douconel.nr_boundary_loops() == 0);
+            assert!(douconel.betti_numbers() == [1, 0, 1]);
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.euler_characteristic() == 1);
+            assert!(douconel.genus() == 0);
+            assert!(douconel.nr_boundary_loops() == 1);
+            assert!(douconel.betti_numbers() == [1, 0, 0]);
+        }
+
+        // A torus, made of a 3x3 grid of quads with opposite sides identified.
+        let faces = (0..3)
+            .flat_map(|i| (0..3).map(move |j| vec![3 * i + j, 3 * ((i + 1) % 3) + j, 3 * ((i + 1) % 3) + (j + 1) % 3, 3 * i + (j + 1) % 3]))
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.euler_characteristic() == 0);
+            assert!(douconel.genus() == 1);
+            assert!(douconel.nr_boundary_loops() == 0);
+            assert!(douconel.betti_numbers() == [1, 2, 1]);
+        }
+    }
+
+    #[test]
+    fn flip_edge() {
+        let faces = vec![vec![0, 1, 2], vec![0, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, _)) = douconel {
+            let [v0, v1, v2, v3] = [0, 1, 2, 3].map(|i| vert_map.get_by_left(&i).copied().unwrap());
+
+            let (boundary_edge, _) = douconel.edge_between_verts(v0, v1).unwrap();
+            assert!(matches!(douconel.flip_edge(boundary_edge), Err(MeshError::BoundaryEdge(..))));
+
+            let (diagonal, _) = douconel.edge_between_verts(v0, v2).unwrap();
+            assert!(douconel.flip_edge(diagonal).is_ok());
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.edge_between_verts(v0, v2).is_none());
+            assert!(douconel.edge_between_verts(v1, v3).is_some());
+            for face_id in douconel.faces.keys() {
+                assert!(douconel.corners(face_id).len() == 3);
+            }
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            assert!(matches!(douconel.flip_edge(edge_id), Err(MeshError::AlreadyConnected(..))));
+        }
+    }
+
+    #[test]
+    fn collapse_edge() {
+        let faces = vec![
+            vec![0, 1, 2],
+            vec![0, 2, 3],
+            vec![0, 3, 4],
+            vec![0, 4, 1],
+            vec![5, 2, 1],
+            vec![5, 3, 2],
+            vec![5, 4, 3],
+            vec![5, 1, 4],
+        ];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, _)) = douconel {
+            let [v0, v1] = [0, 1].map(|i| vert_map.get_by_left(&i).copied().unwrap());
+            let (edge_id, _) = douconel.edge_between_verts(v0, v1).unwrap();
+            let collapsed = douconel.collapse_edge(edge_id);
+            assert!(collapsed.is_ok(), "{collapsed:?}");
+            if let Ok((vert_id, removed)) = collapsed {
+                assert!(vert_id == v0);
+                assert!(removed.verts == vec![v1]);
+                assert!(removed.edges.len() == 6);
+                assert!(removed.faces.len() == 2);
+            }
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.nr_verts() == 5);
+            assert!(douconel.nr_edges() == 9 * 2);
+            assert!(douconel.nr_faces() == 6);
+            assert!(douconel.vneighbors(v0).len() == 4);
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            assert!(matches!(douconel.collapse_edge(edge_id), Err(MeshError::LinkCondition(..))));
+        }
+    }
+
+    #[test]
+    fn delete_and_compact() {
+        let faces = vec![
+            vec![0, 1, 2],
+            vec![0, 2, 3],
+            vec![0, 3, 4],
+            vec![0, 4, 1],
+            vec![5, 2, 1],
+            vec![5, 3, 2],
+            vec![5, 4, 3],
+            vec![5, 1, 4],
+        ];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, face_map)) = douconel {
+            // Deleting two opposite faces around a vertex would pinch it.
+            let pinch = [0, 5].map(|i| face_map.get_by_left(&i).copied().unwrap()).into_iter().collect();
+            assert!(matches!(douconel.delete_faces(&pinch), Err(MeshError::NonManifoldVertex(..))));
+            assert!(douconel.nr_faces() == 8);
+
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let removed = douconel.delete_vertex(v0);
+            assert!(removed.is_ok(), "{removed:?}");
+            if let Ok(removed) = removed {
+                assert!(removed.verts == vec![v0]);
+                assert!(removed.edges.len() == 4 * 2);
+                assert!(removed.faces.len() == 4);
+                // Removed elements are reported as invalid (without modifying the mesh).
+                assert!(matches!(douconel.delete_face(removed.faces[0]), Err(MeshError::InvalidFace(face_id)) if face_id == removed.faces[0]));
+                assert!(matches!(douconel.delete_vertex(v0), Err(MeshError::InvalidVertex(vert_id)) if vert_id == v0));
+                assert!(douconel.nr_faces() == 4);
+            }
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.nr_verts() == 5);
+            assert!(douconel.nr_edges() == 8 * 2);
+            assert!(douconel.nr_faces() == 4);
+            assert!(douconel.boundary_loops().len() == 1);
+            assert!(douconel.boundary_loops()[0].len() == 4);
+
+            let (vert_map, edge_map, face_map) = douconel.compact();
+            assert!(vert_map.len() == 5 && edge_map.len() == 8 * 2 && face_map.len() == 4);
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.boundary_loops()[0].len() == 4);
+        }
+    }
+
+    #[test]
+    fn split_polygons() {
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let face_ids = douconel.face_ids();
+
+            let (_, new_faces) = douconel.split_face(face_ids[0]);
+            assert!(new_faces.len() == 4);
+            for face_id in new_faces {
+                assert!(douconel.corners(face_id).len() == 3);
+            }
+
+            let corners = douconel.corners(face_ids[1]);
+            assert!(matches!(
+                douconel.split_face_by_diagonal(face_ids[1], corners[0], corners[1]),
+                Err(MeshError::NotDiagonal(..))
+            ));
+            let split = douconel.split_face_by_diagonal(face_ids[1], corners[0], corners[2]);
+            assert!(split.is_ok(), "{split:?}");
+            if let Ok((edge_id, [f_0, f_1])) = split {
+                assert!(douconel.endpoints(edge_id) == (corners[0], corners[2]));
+                assert!(douconel.face(edge_id) == f_1);
+                assert!(douconel.corners(f_0).len() == 3);
+                assert!(douconel.corners(f_1).len() == 3);
+            }
+
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.nr_verts() == 8 + 1);
+            assert!(douconel.nr_edges() == (12 + 4 + 1) * 2);
+            assert!(douconel.nr_faces() == 6 + 3 + 1);
+        }
+    }
+
+    #[test]
+    fn attribute_policy() {
+        // A policy that propagates edge labels to the split edges, and marks every other new edge.
+        struct Labels;
+        impl AttributePolicy<Empty, u8, u8> for Labels {
+            fn split_edge_attr(&self, parent: &u8) -> u8 {
+                *parent
+            }
+            fn new_edge_attr(&self) -> u8 {
+                2
+            }
+        }
+
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let face_id = douconel.faces.keys().next().unwrap();
+            let centroid = douconel.centroid(face_id);
+            let (v_x, _) = douconel.split_face_with(face_id, &InterpolatePosition);
+            assert!((douconel.position(v_x) - centroid).norm() < 1e-9);
+
+            let edge_id = douconel.vrep(v_x);
+            let (v_a, v_b) = douconel.endpoints(edge_id);
+            let (v_y, _) = douconel.split_edge_with(edge_id, &InterpolatePosition).unwrap();
+            assert!((douconel.position(v_y) - (douconel.position(v_a) + douconel.position(v_b)) / 2.).norm() < 1e-9);
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, u8, FaceID, u8>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            let twin_id = douconel.twin(edge_id);
+            douconel.edges[edge_id] = 1;
+            douconel.edges[twin_id] = 1;
+            let face_id = douconel.face(edge_id);
+            douconel.faces[face_id] = 7;
+
+            let (v_x, [f_0, _, f_2, _]) = douconel.split_edge_with(edge_id, &Labels).unwrap();
+            assert!(douconel.faces[f_0] == 7 && douconel.faces[f_2] == 7);
+            assert!(douconel.outgoing(v_x).into_iter().filter(|&e| douconel.edges[e] == 1).count() == 2);
+            assert!(douconel.outgoing(v_x).into_iter().filter(|&e| douconel.edges[e] == 2).count() == 2);
+
+            // Without a policy, the new faces of `split_edge` get default data.
+            let (_, [_, _, f_2, f_3]) = douconel.split_edge(douconel.frep(f_0)).unwrap();
+            assert!(douconel.faces[f_2] == 0 && douconel.faces[f_3] == 0);
+        }
+
+        let faces = vec![vec![0, 1, 2], vec![0, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, u8, FaceID, u8>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, face_map)) = douconel {
+            let [v0, v2] = [0, 2].map(|i| vert_map.get_by_left(&i).copied().unwrap());
+            for (index, face_data) in [(0, 5), (1, 6)] {
+                douconel.faces[face_map.get_by_left(&index).copied().unwrap()] = face_data;
+            }
+            let boundary_edge_id = douconel.edge_ids().into_iter().find(|&edge_id| douconel.is_boundary_edge(edge_id)).unwrap();
+            assert!(matches!(douconel.split_edge(boundary_edge_id), Err(MeshError::BoundaryEdge(..))));
+            let (diagonal, twin_id) = douconel.edge_between_verts(v0, v2).unwrap();
+            assert!(douconel.flip_edge_with(diagonal, &Labels).is_ok());
+            assert!(douconel.edges[diagonal] == 2 && douconel.edges[twin_id] == 2);
+
+            let kept = douconel.faces[douconel.face(diagonal)];
+            let merged = douconel.merge_faces_with(diagonal, &Labels);
+            assert!(merged.is_ok(), "{merged:?}");
+            if let Ok((face_id, _)) = merged {
+                assert!(douconel.faces[face_id] == kept);
+            }
+        }
+
+        // An octahedron: collapse the edge between two of its corners.
+        let faces = vec![
+            vec![0, 1, 2],
+            vec![0, 2, 3],
+            vec![0, 3, 4],
+            vec![0, 4, 1],
+            vec![5, 2, 1],
+            vec![5, 3, 2],
+            vec![5, 4, 3],
+            vec![5, 1, 4],
+        ];
+        let positions = vec![
+            Vector3D::new(0., 0., 1.),
+            Vector3D::new(1., 0., 0.),
+            Vector3D::new(0., 1., 0.),
+            Vector3D::new(-1., 0., 0.),
+            Vector3D::new(0., -1., 0.),
+            Vector3D::new(0., 0., -1.),
+        ];
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_embedded_faces(&faces, &positions);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, _)) = douconel {
+            let [v0, v1] = [0, 1].map(|i| vert_map.get_by_left(&i).copied().unwrap());
+            let (edge_id, _) = douconel.edge_between_verts(v0, v1).unwrap();
+            let collapsed = douconel.collapse_edge_with(edge_id, &InterpolatePosition);
+            assert!(collapsed.is_ok(), "{collapsed:?}");
+            if let Ok((vert_id, _)) = collapsed {
+                assert!((douconel.position(vert_id) - Vector3D::new(0.5, 0., 0.5)).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn split_edge_at() {
+        // A policy that interpolates the positions, and marks the new diagonals.
+        struct Diagonals;
+        impl AttributePolicy<EmbeddedVertex, u8, Empty> for Diagonals {
+            fn interpolate_vertex(&self, a: &EmbeddedVertex, b: &EmbeddedVertex, t: f64) -> EmbeddedVertex {
+                AttributePolicy::<EmbeddedVertex, u8, Empty>::interpolate_vertex(&InterpolatePosition, a, b, t)
+            }
+            fn new_edge_attr(&self) -> u8 {
+                1
+            }
+        }
+        // A vertex with a position and a label.
+        #[derive(Default, Clone, Debug)]
+        struct LabelledVertex {
+            position: Vector3D,
+            label: u8,
+        }
+        impl HasPosition for LabelledVertex {
+            fn position(&self) -> Vector3D {
+                self.position
+            }
+            fn set_position(&mut self, position: Vector3D) {
+                self.position = position;
+            }
+        }
+
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/tetrahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            let (v_a, v_b) = douconel.endpoints(edge_id);
+            let (v_x, faces) = douconel.split_edge_at(edge_id, 0.25);
+            assert!(douconel.nr_verts() == 5);
+            assert!(douconel.nr_faces() == 6);
+            assert!(faces.len() == 4 && faces.iter().all(|&face_id| douconel.corners(face_id).len() == 3));
+            assert!((douconel.position(v_x) - douconel.position(v_a).lerp(&douconel.position(v_b), 0.25)).norm() < 1e-9);
+            assert!(douconel.validate().is_valid());
+        }
+
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            let (_, faces) = douconel.split_edge_at(edge_id, 0.5);
+            assert!(douconel.nr_verts() == 9);
+            assert!(douconel.nr_faces() == 6);
+            assert!(faces.len() == 2 && faces.iter().all(|&face_id| douconel.corners(face_id).len() == 5));
+            assert!(douconel.validate().is_valid());
+        }
+
+        let faces = vec![vec![0, 1, 2]];
+        let positions = vec![Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.)];
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_embedded_faces(&faces, &positions);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().find(|&edge_id| douconel.is_boundary(edge_id)).unwrap();
+            let (v_x, faces) = douconel.split_edge_at(edge_id, 0.5);
+            assert!(douconel.nr_faces() == 2 && faces.len() == 2);
+            assert!(douconel.is_boundary_vertex(v_x));
+            assert!(douconel.boundary_loops()[0].len() == 4);
+            assert!(douconel.validate().is_valid());
+        }
+
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, u8, FaceID, Empty>::from_file(&PathBuf::from("assets/tetrahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            let (v_a, v_b) = douconel.endpoints(edge_id);
+            let (v_x, _) = douconel.split_edge_at_with(edge_id, 0.75, &Diagonals);
+            assert!((douconel.position(v_x) - douconel.position(v_a).lerp(&douconel.position(v_b), 0.75)).norm() < 1e-9);
+            assert!(douconel.outgoing(v_x).into_iter().filter(|&e| douconel.edges[e] == 1).count() == 2);
+        }
+
+        // Two triangles that share all three edges have the same opposite corner: only the first one is split.
+        let faces = vec![vec![0, 1, 2], vec![0, 2, 1]];
+        let positions = vec![Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.)];
+        let douconel = Douconel::<VertID, LabelledVertex, EdgeID, Empty, FaceID, Empty>::from_embedded_faces(&faces, &positions);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let edge_id = douconel.edges.keys().next().unwrap();
+            let (v_a, v_b) = douconel.endpoints(edge_id);
+            douconel.verts[v_a].label = 1;
+            douconel.verts[v_b].label = 2;
+            let (v_x, faces) = douconel.split_edge_at(edge_id, 0.25);
+            assert!(douconel.nr_faces() == 3 && faces.len() == 3);
+            assert!(douconel.validate().is_valid());
+            // The new vertex keeps the data of the nearer endpoint (except for its position).
+            assert!(douconel.verts[v_x].label == 1);
+            assert!((douconel.position(v_x) - douconel.position(v_a).lerp(&douconel.position(v_b), 0.25)).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn merge_faces() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, face_map)) = douconel {
+            let [v0, v1] = [0, 1].map(|i| vert_map.get_by_left(&i).copied().unwrap());
+            let (edge_id, _) = douconel.edge_between_verts(v0, v1).unwrap();
+            let merged = douconel.merge_faces(edge_id);
+            assert!(merged.is_ok(), "{merged:?}");
+            let (quad, removed) = merged.unwrap();
+            assert!(douconel.corners(quad).len() == 4);
+            assert!(removed.edges.len() == 2);
+            assert!(removed.faces.len() == 1);
+            douconel.assert_properties();
+            douconel.assert_references();
+            douconel.assert_invariants();
+            assert!(douconel.nr_edges() == 5 * 2);
+            assert!(douconel.nr_faces() == 3);
+
+            // The quad shares two edges with each remaining triangle.
+            let f2 = face_map.get_by_left(&2).copied().unwrap();
+            let edge_id = douconel.edges(f2).into_iter().find(|&e| douconel.face(douconel.twin(e)) == quad).unwrap();
+            assert!(matches!(douconel.merge_faces(edge_id), Err(MeshError::NotMergeable(..))));
+        }
+    }
+
+    #[test]
+    fn try_accessors() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, face_map)) = douconel {
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let f3 = face_map.get_by_left(&3).copied().unwrap();
+            let e3 = douconel.frep(f3);
+
+            assert!(douconel.try_corners(f3).is_ok_and(|corners| corners.len() == 3));
+            assert!(douconel.try_outgoing(v0).is_ok_and(|outgoing| outgoing.len() == 3));
+            assert!(douconel.try_face(e3).is_ok_and(|face_id| face_id == f3));
+            assert!(douconel.try_nexts(e3).is_ok_and(|nexts| nexts == douconel.nexts(e3)));
+            assert!(douconel.try_faces(e3).is_ok_and(|faces| faces == douconel.faces(e3)));
+            assert!(douconel.try_quad(e3).is_ok_and(|quad| quad == douconel.quad(e3)));
+            assert!(douconel.try_prev(e3).is_ok_and(|prev| prev == douconel.prev(e3)));
+
+            assert!(douconel.delete_face(f3).is_ok());
+            assert!(matches!(douconel.try_corners(f3), Err(MeshError::InvalidFace(face_id)) if face_id == f3));
+            assert!(matches!(douconel.try_frep(f3), Err(MeshError::InvalidFace(face_id)) if face_id == f3));
+            assert!(matches!(douconel.try_face(e3), Err(MeshError::BoundaryEdge(..))));
+            assert!(matches!(douconel.try_faces(douconel.twin(e3)), Err(MeshError::BoundaryEdge(..))));
+            assert!(douconel.face_with_verts(&[]).is_none());
+
+            assert!(douconel.delete_vertex(v0).is_ok());
+            assert!(matches!(douconel.try_outgoing(v0), Err(MeshError::InvalidVertex(vert_id)) if vert_id == v0));
+            assert!(matches!(douconel.try_root(e3), Err(MeshError::InvalidEdge(edge_id)) if edge_id == e3));
+            assert!(douconel.face_with_verts(&[v0]).is_none());
+
+            // A reference to a removed element is reported on that element (instead of panicking).
+            let f = douconel.faces.keys().next().unwrap();
+            let e = douconel.frep(f);
+            douconel.edge_next.insert(e, e3);
+            assert!(matches!(douconel.try_corners(f), Err(MeshError::InvalidEdge(edge_id)) if edge_id == e3));
+            assert!(matches!(douconel.try_fneighbors(f), Err(MeshError::InvalidEdge(edge_id)) if edge_id == e3));
+            assert!(matches!(douconel.try_next(e), Err(MeshError::InvalidEdge(edge_id)) if edge_id == e3));
+        }
+    }
+
+    #[test]
+    fn circulators() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _)) = douconel {
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let v3 = vert_map.get_by_left(&3).copied().unwrap();
+            assert!(douconel.outgoing_iter(v0).count() == 3);
+            assert!(douconel.star_iter(v0).count() == 2);
+
+            for vert_id in douconel.vert_ids() {
+                assert!(douconel.outgoing_iter(vert_id).eq(douconel.outgoing(vert_id)));
+                assert!(douconel.vneighbors_iter(vert_id).eq(douconel.vneighbors(vert_id)));
+            }
+            for face_id in douconel.face_ids() {
+                let edges = douconel.edges(face_id);
+                assert!(edges.len() == 3);
+                assert!(douconel.nexts_iter(edges[0]).eq(edges[1..].iter().copied()));
+                assert!(douconel.corners_iter(face_id).eq(douconel.corners(face_id)));
+                assert!(douconel.fneighbors_iter(face_id).count() == 2);
+            }
+
+            let (e_03, e_30) = douconel.edge_between_verts(v0, v3).unwrap();
+            assert!(douconel.endpoints(e_03) == (v0, v3) && douconel.twin(e_03) == e_30);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        // Two 12-gons glued along their boundary.
+        let faces = vec![(0..12).collect::<Vec<_>>(), (0..12).rev().collect::<Vec<_>>()];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            douconel.assert_invariants();
+            let options = ValidationOptions {
+                manifold: true,
+                connected: true,
+            };
+            let report = douconel.validate_with(options);
+            assert!(report.is_valid(), "{report:?}");
+
+            // Skipping an edge of a face: only the skipped edge is not on a cycle.
+            let mut douconel = douconel;
+            let e = douconel.edge_ids()[0];
+            let skipped = douconel.next(e);
+            douconel.edge_next.insert(e, douconel.next(skipped));
+            let report = douconel.validate();
+            assert!(report.of(Invariant::NextCycle) == vec![Element::Edge(skipped)], "{report:?}");
+        }
+
+        // Corrupt a tetrahedron: every violation is reported by element and invariant.
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, vert_map, face_map)) = douconel {
+            let v0 = vert_map.get_by_left(&0).copied().unwrap();
+            let f0 = face_map.get_by_left(&0).copied().unwrap();
+            let f1 = face_map.get_by_left(&1).copied().unwrap();
+            let e = douconel.frep(f1);
+            let (twin, next) = (douconel.twin(e), douconel.next(e));
+
+            douconel.edge_twin.insert(e, next);
+            douconel.face_rep.remove(f0);
+            douconel.vert_rep.remove(v0);
+
+            let report = douconel.validate();
+            assert!(!report.is_valid());
+            for (element, invariant) in [
+                (Element::Edge(e), Invariant::TwinOfTwin),
+                (Element::Edge(twin), Invariant::TwinOfTwin),
+                (Element::Face(f0), Invariant::MissingFrep),
+                (Element::Vert(v0), Invariant::MissingVrep),
+            ] {
+                assert!(
+                    report.violations.contains(&Violation { element, invariant }),
+                    "{element:?} {invariant:?} {report:?}"
+                );
+            }
+            assert!(report.of(Invariant::MissingFrep) == vec![Element::Face(f0)]);
+            assert!(report.of(Invariant::MissingVrep) == vec![Element::Vert(v0)]);
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let positions = vec![
+            Vector3D::new(0., 0., 0.),
+            Vector3D::new(1., 0., 0.),
+            Vector3D::new(0., 1., 0.),
+            Vector3D::new(2., 0., 0.),
+        ];
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_embedded_faces(&faces, &positions);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, face_map)) = douconel {
+            let report = douconel.validate_embedded(ValidationOptions::default(), 1e-9);
+            let f1 = face_map.get_by_left(&1).copied().unwrap();
+            assert!(report.of(Invariant::DegenerateFace) == vec![Element::Face(f1)], "{report:?}");
+        }
+    }
+
     #[test]
     fn from_blub_stl() {
         let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/blub001k.stl"));
@@ -97,6 +825,770 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dual() {
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let dual = douconel.dual_embedded();
+            assert!(dual.is_ok(), "{dual:?}");
+            if let Ok((dual, face_to_dual, vert_to_dual)) = dual {
+                assert!(dual.nr_verts() == 6);
+                assert!(dual.nr_edges() == 12 * 2);
+                assert!(dual.nr_faces() == 8);
+                assert!(face_to_dual.len() == 6 && vert_to_dual.len() == 8);
+                for (&vert_id, &dual_face_id) in &vert_to_dual {
+                    assert!(dual.corners(dual_face_id).len() == 3);
+                    assert!(dual.normal(dual_face_id).dot(&douconel.vert_normal(vert_id)) > 0.);
+                }
+                for (&face_id, &dual_vert_id) in &face_to_dual {
+                    assert!(dual.position(dual_vert_id) == douconel.centroid(face_id));
+                }
+            }
+        }
+
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _)) = douconel {
+            let dual = douconel.dual();
+            assert!(dual.is_ok(), "{dual:?}");
+            if let Ok((dual, face_to_dual, vert_to_dual)) = dual {
+                assert!(dual.nr_verts() == 3);
+                assert!(dual.nr_faces() == 1);
+                assert!(face_to_dual.len() == 3);
+                assert!(vert_to_dual.left_values().copied().collect_vec() == vec![vert_map.get_by_left(&1).copied().unwrap()]);
+            }
+        }
+    }
+
+    #[test]
+    fn submesh() {
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, face_map)) = douconel {
+            let f0 = face_map.get_by_left(&0).copied().unwrap();
+            let f1 = douconel.fneighbors(f0)[0];
+            let f2 = douconel
+                .faces
+                .keys()
+                .find(|&face_id| face_id != f0 && douconel.edge_between_faces(f0, face_id).is_none())
+                .unwrap();
+
+            let submesh = douconel.submesh(&HashSet::from([f0, f1]));
+            assert!(submesh.is_ok(), "{submesh:?}");
+            if let Ok((mut submesh, vert_map, edge_map, face_map)) = submesh {
+                assert!(submesh.nr_verts() == 6);
+                assert!(submesh.nr_edges() == 7 * 2);
+                assert!(submesh.nr_faces() == 2);
+                assert!(submesh.boundary_loops().len() == 1);
+                assert!(vert_map.len() == 6 && edge_map.len() == 7 * 2 && face_map.len() == 2);
+                for (&child_edge_id, &edge_id) in &edge_map {
+                    let (u, v) = douconel.endpoints(edge_id);
+                    assert!(submesh.endpoints(child_edge_id) == (vert_map.get_by_right(&u).copied().unwrap(), vert_map.get_by_right(&v).copied().unwrap()));
+                }
+
+                // Process the submesh, and write the result back to the parent.
+                for (&child_vert_id, &vert_id) in &vert_map {
+                    assert!(submesh.position(child_vert_id) == douconel.position(vert_id));
+                    let position = submesh.position(child_vert_id);
+                    submesh.verts[child_vert_id].set_position(position * 2.);
+                }
+                for (&child_vert_id, &vert_id) in &vert_map {
+                    douconel.verts[vert_id] = submesh.verts[child_vert_id];
+                }
+            }
+
+            let submesh = douconel.submesh(&HashSet::from([f0, f2]));
+            assert!(submesh.is_ok_and(|(submesh, _, _, _)| submesh.nr_faces() == 2 && submesh.components().len() == 2));
+
+            assert!(douconel.delete_face(f2).is_ok());
+            assert!(matches!(douconel.submesh(&HashSet::from([f0, f2])), Err(MeshError::InvalidFace(..))));
+        }
+    }
+
+    #[test]
+    fn append_and_glue() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let vert_ids = douconel.vert_ids();
+            let labels = douconel.add_vertex_property::<usize>("label");
+            for (label, &vert_id) in vert_ids.iter().enumerate() {
+                labels[vert_id] = label + 1;
+            }
+            let mut other = douconel.clone();
+            other.add_face_property::<bool>("selected").values_mut().for_each(|selected| *selected = true);
+            let (vert_map, edge_map, face_map) = douconel.append(other);
+            assert!(vert_map.len() == 4 && edge_map.len() == 6 * 2 && face_map.len() == 4);
+            assert!(douconel.nr_verts() == 8);
+            // The appended elements keep their property values (also of layers that only the other mesh has).
+            let labels = douconel.vertex_property::<usize>("label").unwrap();
+            assert!(vert_map.iter().all(|(&other_id, &vert_id)| labels[vert_id] == labels[other_id]));
+            let selected = douconel.face_property::<bool>("selected").unwrap();
+            assert!(selected.len() == 8);
+            assert!(
+                douconel
+                    .face_ids()
+                    .into_iter()
+                    .all(|face_id| selected[face_id] == face_map.contains_right(&face_id))
+            );
+            assert!(douconel.components().len() == 2);
+            assert!(
+                douconel
+                    .validate_with(ValidationOptions {
+                        manifold: true,
+                        connected: false
+                    })
+                    .is_valid()
+            );
+        }
+
+        // Cut a hexahedron into two halves (three faces around opposite corners), and glue them back together.
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let corner = douconel.vert_ids()[0];
+            let star = douconel.star(corner).into_iter().collect::<HashSet<_>>();
+            let rest = douconel.faces.keys().filter(|face_id| !star.contains(face_id)).collect::<HashSet<_>>();
+            let (Ok((mut half_a, _, edge_map_a, _)), Ok((half_b, _, edge_map_b, _))) = (douconel.submesh(&star), douconel.submesh(&rest)) else {
+                panic!("submesh failed");
+            };
+            assert!(half_a.boundary_loops().len() == 1 && half_b.boundary_loops().len() == 1);
+
+            let boundary_a = half_a.boundary_loops()[0][0];
+            let boundary_b = edge_map_b
+                .get_by_right(&douconel.twin(edge_map_a.get_by_left(&boundary_a).copied().unwrap()))
+                .copied()
+                .unwrap();
+            let interior = half_a.frep(half_a.faces.keys().next().unwrap());
+            assert!(matches!(
+                half_a.glue(half_a.clone(), boundary_a, interior),
+                Err(MeshError::NotBoundaryEdge(edge_id)) if edge_id == interior
+            ));
+            let (single, _, _, _) = douconel.submesh(&rest.iter().take(1).copied().collect()).unwrap();
+            let boundary_single = single.boundary_loops()[0][0];
+            assert!(matches!(
+                half_a.glue(single, boundary_a, boundary_single),
+                Err(MeshError::BoundaryLengthMismatch(6, 4))
+            ));
+
+            let glued = half_a.glue(half_b, boundary_a, boundary_b);
+            assert!(glued.is_ok(), "{glued:?}");
+            if let Ok((vert_map, edge_map, face_map)) = glued {
+                assert!(half_a.nr_verts() == 8);
+                assert!(half_a.nr_edges() == 12 * 2);
+                assert!(half_a.nr_faces() == 6);
+                assert!(half_a.boundary_loops().is_empty());
+                assert!(half_a.validate().is_valid());
+                assert!(half_a.genus() == 0);
+                assert!(vert_map.len() == 7 && edge_map.len() == 9 * 2 && face_map.len() == 3);
+                assert!(vert_map.right_values().all(|&vert_id| half_a.verts.contains_key(vert_id)));
+                assert!(edge_map.right_values().all(|&edge_id| half_a.edges.contains_key(edge_id)));
+            }
+        }
+
+        // Two squares with a diagonal, glued into a tetrahedron: only if their diagonals do not coincide.
+        let faces = vec![vec![0, 1, 2], vec![0, 2, 3]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let boundary_loop = douconel.boundary_loops()[0].clone();
+            let mut nr_glued = 0;
+            for boundary_b in boundary_loop {
+                let mut square = douconel.clone();
+                match square.glue(douconel.clone(), douconel.boundary_loops()[0][0], boundary_b) {
+                    Ok(_) => {
+                        nr_glued += 1;
+                        assert!(square.nr_verts() == 4 && square.nr_edges() == 6 * 2 && square.nr_faces() == 4);
+                        assert!(
+                            square
+                                .validate_with(ValidationOptions {
+                                    manifold: true,
+                                    connected: true
+                                })
+                                .is_valid()
+                        );
+                    }
+                    Err(MeshError::DuplicateEdge(..)) => {
+                        assert!(square.nr_verts() == 4 && square.nr_edges() == 5 * 2 && square.nr_faces() == 2);
+                    }
+                    Err(err) => panic!("{err:?}"),
+                }
+            }
+            assert!(nr_glued == 2);
+        }
+    }
+
+    #[test]
+    fn property_layers() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let curvature = douconel.add_vertex_property::<f64>("curvature");
+            assert!(curvature.len() == 4);
+            for value in curvature.values_mut() {
+                *value = 1.;
+            }
+            assert!(douconel.add_face_property_serialized::<usize>("label").is_ok());
+            assert!(douconel.vertex_property::<usize>("curvature").is_none());
+            assert!(douconel.face_property::<f64>("missing").is_none());
+
+            // New elements get the default value.
+            let (v_x, new_faces) = douconel.split_face(douconel.face_ids()[0]);
+            assert!(douconel.vertex_property::<f64>("curvature").unwrap().len() == 5);
+            assert!(douconel.vertex_property::<f64>("curvature").unwrap()[v_x] == 0.);
+            assert!(douconel.face_property::<usize>("label").unwrap().len() == douconel.nr_faces());
+            douconel.face_property_mut::<usize>("label").unwrap()[new_faces[0]] = 7;
+
+            // Removed elements are removed from the layers.
+            let removed = douconel.delete_vertex(v_x);
+            assert!(removed.is_ok(), "{removed:?}");
+            assert!(douconel.vertex_property::<f64>("curvature").unwrap().len() == douconel.nr_verts());
+            assert!(douconel.face_property::<usize>("label").unwrap().len() == douconel.nr_faces());
+
+            let f_0 = douconel.face_ids()[0];
+            douconel.face_property_mut::<usize>("label").unwrap()[f_0] = 3;
+            let (vert_map, _, face_map) = douconel.compact();
+            let f_0 = face_map.get_by_left(&f_0).copied().unwrap();
+            assert!(douconel.face_property::<usize>("label").unwrap()[f_0] == 3);
+            assert!(
+                vert_map
+                    .right_values()
+                    .all(|&vert_id| (douconel.vertex_property::<f64>("curvature").unwrap()[vert_id] - 1.).abs() < f64::EPSILON)
+            );
+
+            // Only serialized layers survive a round-trip.
+            let f_1 = douconel.face_ids().into_iter().find(|&face_id| face_id != f_0).unwrap();
+            douconel.face_property_mut::<usize>("label").unwrap()[f_1] = 5;
+            let serialized = serde_json::to_string(&douconel);
+            assert!(serialized.is_ok(), "{serialized:?}");
+            if let Ok(serialized) = serialized {
+                let deserialized = serde_json::from_str::<Douconel<VertID, Empty, EdgeID, Empty, FaceID, Empty>>(&serialized);
+                assert!(deserialized.is_ok(), "{deserialized:?}");
+                if let Ok(mut deserialized) = deserialized {
+                    assert!(deserialized.vertex_property::<f64>("curvature").is_none());
+
+                    // Until it is added again, the layer follows the removal and compaction of elements.
+                    assert!(deserialized.delete_face(f_1).is_ok());
+                    let (_, _, face_map) = deserialized.compact();
+                    let f_0 = face_map.get_by_left(&f_0).copied().unwrap();
+                    // Data of a different type is not restored (and kept until the layer is added with the right type).
+                    assert!(deserialized.add_face_property_serialized::<String>("label").is_err());
+                    assert!(deserialized.face_property::<String>("label").is_none());
+                    let label = deserialized.add_face_property_serialized::<usize>("label");
+                    assert!(label.is_ok(), "{label:?}");
+                    if let Ok(label) = label {
+                        assert!(label[f_0] == 3);
+                        assert!(label.values().filter(|&&value| value != 0).count() == 1);
+                    }
+                }
+            }
+
+            // A layer with values that cannot be serialized (JSON maps need string keys) fails serialization.
+            let mut unserializable = douconel.clone();
+            let pairs = unserializable.add_face_property_serialized::<HashMap<(u8, u8), u8>>("pairs");
+            assert!(pairs.is_ok());
+            if let Ok(pairs) = pairs {
+                pairs.values_mut().for_each(|value| {
+                    value.insert((0, 1), 2);
+                });
+            }
+            assert!(serde_json::to_string(&unserializable).is_err());
+
+            assert!(douconel.remove_vertex_property("curvature"));
+            assert!(!douconel.remove_vertex_property("curvature"));
+        }
+    }
+
+    #[test]
+    fn undirected_edges() {
+        use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let undirected = douconel.undirected_edges();
+            assert!(undirected.len() == 12);
+            assert!(douconel.edges_positions().len() == 12);
+            for edge_id in douconel.edge_ids() {
+                assert!(douconel.canonical(edge_id) == douconel.canonical(douconel.twin(edge_id)));
+                assert!(undirected.contains(&edge_id) != undirected.contains(&douconel.twin(edge_id)));
+            }
+
+            let mut lengths = UndirectedEdgeMap::from_fn(&douconel, |edge_id| douconel.length(edge_id));
+            assert!(lengths.len() == 12);
+            let e_ab = douconel.edge_ids()[0];
+            let e_ba = douconel.twin(e_ab);
+            assert!(lengths.get(&douconel, e_ab) == lengths.get(&douconel, e_ba));
+            assert!(lengths.insert(&douconel, e_ba, 0.).is_some());
+            assert!(lengths.get(&douconel, e_ab) == Some(&0.));
+            assert!(lengths.remove(&douconel, e_ab).is_some());
+            assert!(!lengths.contains_key(&douconel, e_ba));
+            assert!(lengths.len() == 11);
+
+            // Edges that are added to the mesh after the map was created are supported.
+            let mut douconel = douconel;
+            let face_id = douconel.face_ids()[0];
+            let (vert_id, _) = douconel.split_face(face_id);
+            let new_edge_id = douconel.outgoing(vert_id)[0];
+            assert!(lengths.insert(&douconel, douconel.twin(new_edge_id), 1.).is_none());
+            assert!(lengths.get(&douconel, new_edge_id) == Some(&1.));
+            assert!(lengths.len() == 12);
+
+            // The directed graph has an edge for every half-edge, with its own weight.
+            let (graph, key_to_int) = douconel.graph_with_weights(|edge_id| edge_id);
+            assert!(graph.edge_count() == douconel.edge_ids().len());
+            assert!(graph.edge_references().all(|edge| {
+                let (u, v) = douconel.endpoints(*edge.weight());
+                key_to_int.get_by_left(&u) == Some(&edge.source()) && key_to_int.get_by_left(&v) == Some(&edge.target())
+            }));
+            // A filtered half-edge is left out (but its twin is not).
+            let (graph, _) = douconel.graph_modified(&HashSet::new(), &HashSet::from([e_ab]), |edge_id| edge_id);
+            assert!(graph.edge_count() == douconel.edge_ids().len() - 1);
+            assert!(graph.edge_references().all(|edge| *edge.weight() != e_ab));
+            assert!(graph.edge_references().any(|edge| *edge.weight() == e_ba));
+
+            // The undirected graph has an edge for every pair of twins, with the weight evaluated once (on the canonical half-edge).
+            let evaluated = std::cell::Cell::new(0);
+            let (graph, _) = douconel.undirected_graph_with_weights(|edge_id| {
+                evaluated.set(evaluated.get() + 1);
+                edge_id
+            });
+            assert!(evaluated.get() == douconel.undirected_edges().len());
+            assert!(graph.edge_references().all(|edge| douconel.canonical(*edge.weight()) == *edge.weight()));
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            let original = douconel.clone();
+            assert!(!douconel.undo() && !douconel.redo());
+            assert!(matches!(douconel.commit(), Err(MeshError::NoTransaction)));
+            assert!(matches!(douconel.rollback(), Err(MeshError::NoTransaction)));
+
+            assert!(douconel.begin().is_ok());
+            assert!(matches!(douconel.begin(), Err(MeshError::TransactionOpen)));
+            let e_ab = douconel.edge_ids()[0];
+            let (v_x, _) = douconel.split_edge(e_ab).unwrap();
+            let (v_y, _) = douconel.split_face(douconel.face_ids()[0]);
+            assert!(douconel.commit().is_ok());
+            let split = douconel.clone();
+            assert!(douconel.can_undo() && !douconel.can_redo());
+            // A clone starts with an empty history.
+            assert!(!split.can_undo());
+
+            // Undo restores the exact same IDs.
+            assert!(douconel.undo());
+            assert!(douconel.nr_verts() == 4 && douconel.nr_edges() == 12 && douconel.nr_faces() == 4);
+            assert!(douconel.edge_ids() == original.edge_ids() && douconel.face_ids() == original.face_ids());
+            assert!(
+                douconel
+                    .edge_ids()
+                    .iter()
+                    .all(|&edge_id| douconel.endpoints(edge_id) == original.endpoints(edge_id))
+            );
+            assert!(douconel.validate().is_valid());
+
+            assert!(douconel.redo());
+            assert!(douconel.verts.contains_key(v_x) && douconel.verts.contains_key(v_y));
+            assert!(douconel.edge_ids() == split.edge_ids());
+            assert!(douconel.edge_ids().iter().all(|&edge_id| douconel.next(edge_id) == split.next(edge_id)));
+            assert!(douconel.validate().is_valid());
+
+            // Rollback reverts the open transaction, and new elements get the same IDs again.
+            assert!(douconel.begin().is_ok());
+            let (v_z, _) = douconel.split_face(douconel.face_ids()[0]);
+            assert!(!douconel.undo());
+            assert!(douconel.rollback().is_ok());
+            assert!(!douconel.verts.contains_key(v_z));
+            assert!(douconel.nr_verts() == split.nr_verts());
+            assert!(douconel.split_face(douconel.face_ids()[0]).0 == v_z);
+
+            // Edits outside a transaction are not recorded, and clear the undo and redo stacks (their changes no longer apply).
+            assert!(!douconel.can_undo() && !douconel.undo());
+
+            // Property layers, and edits that only change the connectivity, are reverted and reapplied as well.
+            douconel.add_vertex_property::<u32>("label");
+            let v_a = douconel.vert_ids()[0];
+            let e_ab = douconel
+                .edge_ids()
+                .into_iter()
+                .find(|&edge_id| douconel.clone().flip_edge(edge_id).is_ok())
+                .unwrap();
+            let endpoints = douconel.endpoints(e_ab);
+            assert!(douconel.begin().is_ok());
+            douconel.vertex_property_mut::<u32>("label").unwrap()[v_a] = 1;
+            assert!(douconel.flip_edge(e_ab).is_ok());
+            assert!(douconel.commit().is_ok());
+            assert!(douconel.undo());
+            assert!(douconel.vertex_property::<u32>("label").unwrap()[v_a] == 0);
+            assert!(douconel.endpoints(e_ab) == endpoints);
+            assert!(douconel.validate().is_valid());
+            assert!(douconel.redo());
+            assert!(douconel.vertex_property::<u32>("label").unwrap()[v_a] == 1);
+            assert!(douconel.endpoints(e_ab) != endpoints);
+            assert!(douconel.validate().is_valid());
+        }
+    }
+
+    #[test]
+    fn seeded_sampling() {
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/tetrahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let sample = |seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (
+                    douconel.random_verts_with(2, &mut rng),
+                    douconel.random_edges_with(3, &mut rng),
+                    douconel.random_faces_with(2, &mut rng),
+                    douconel.random_walk_with(douconel.vert_ids()[0], 10, &mut rng),
+                    douconel.random_surface_points_with(10, &mut rng),
+                )
+            };
+            let (verts, edges, faces, walk, points) = sample(42);
+            assert!(verts.len() == 2 && edges.len() == 3 && faces.len() == 2);
+            assert!(sample(42) == (verts, edges, faces, walk.clone(), points.clone()));
+
+            assert!(walk.len() == 11);
+            assert!(walk.iter().tuple_windows().all(|(&a, &b)| douconel.vneighbors(a).contains(&b)));
+
+            assert!(points.len() == 10);
+            for (face_id, barycentric) in points {
+                assert!(barycentric.iter().all(|&weight| (0. ..=1.).contains(&weight)));
+                assert!((barycentric.iter().sum::<f64>() - 1.).abs() < 1e-9);
+                let point = douconel.surface_point(face_id, &barycentric);
+                assert!((douconel.normal(face_id).dot(&(point - douconel.position(douconel.corners(face_id)[0])))).abs() < 1e-9);
+            }
+        }
+
+        // On quads, points are spread over the whole face (both triangles of its fan), and over all faces.
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let points = douconel.random_surface_points_with(1200, &mut StdRng::seed_from_u64(42));
+            assert!(points.len() == 1200);
+            let mut in_second_triangle = 0;
+            for (face_id, barycentric) in &points {
+                assert!(barycentric.len() == 4);
+                assert!(barycentric.iter().all(|&weight| (0. ..=1.).contains(&weight)));
+                assert!((barycentric.iter().sum::<f64>() - 1.).abs() < 1e-9);
+                let point = douconel.surface_point(*face_id, barycentric);
+                assert!((douconel.normal(*face_id).dot(&(point - douconel.position(douconel.corners(*face_id)[0])))).abs() < 1e-9);
+                if barycentric[3] > 0. {
+                    in_second_triangle += 1;
+                }
+            }
+            let per_face = points.iter().map(|(face_id, _)| face_id).counts();
+            assert!(per_face.len() == 6 && per_face.values().all(|&count| (150..250).contains(&count)));
+            assert!((480..720).contains(&in_second_triangle));
+        }
+    }
+
+    #[test]
+    fn edge_loops_and_rings() {
+        // A 3x3 grid of quads, its four interior vertices are regular.
+        let v = |x: usize, y: usize| y * 4 + x;
+        let faces = (0..3)
+            .cartesian_product(0..3)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _)) = douconel {
+            let vert = |x, y| vert_map.get_by_left(&v(x, y)).copied().unwrap();
+            let e_ab = douconel.edge_between_verts(vert(1, 1), vert(2, 1)).unwrap().0;
+
+            let edge_loop = douconel.edge_loop(e_ab);
+            assert!(edge_loop.len() == 3);
+            assert!(douconel.root(edge_loop[0]) == vert(0, 1) && douconel.toor(edge_loop[2]) == vert(3, 1));
+            assert!(edge_loop.iter().tuple_windows().all(|(&a, &b)| douconel.toor(a) == douconel.root(b)));
+
+            let edge_ring = douconel.edge_ring(e_ab);
+            assert!(edge_ring.len() == 4);
+            assert!(edge_ring.contains(&e_ab));
+            assert!(edge_ring.iter().map(|&edge_id| douconel.endpoints(edge_id)).collect_vec() == (0..4).map(|y| (vert(1, y), vert(2, y))).collect_vec());
+
+            // Loops stop at the irregular (boundary) vertices, rings at the boundary.
+            let e_boundary = douconel.edge_between_verts(vert(0, 0), vert(0, 1)).unwrap().0;
+            assert!(douconel.edge_loop(e_boundary) == vec![e_boundary]);
+            assert!(douconel.edge_ring(e_boundary).len() == 4);
+        }
+
+        // A 4x4 quad torus, all vertices are regular so all loops and rings close.
+        let v = |x: usize, y: usize| (y % 4) * 4 + (x % 4);
+        let faces = (0..4)
+            .cartesian_product(0..4)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            for edge_id in douconel.edge_ids() {
+                assert!(douconel.edge_loop(edge_id).len() == 4);
+                assert!(douconel.edge_ring(edge_id).len() == 4);
+            }
+        }
+
+        // The hexahedron has only irregular (valence 3) vertices, but its rings go around the cube.
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            for edge_id in douconel.edge_ids() {
+                assert!(douconel.edge_loop(edge_id) == vec![edge_id]);
+                assert!(douconel.edge_ring(edge_id).len() == 4);
+            }
+        }
+    }
+
+    #[test]
+    fn quad_patch_layout() {
+        // A cube with every side split into 2x2 quads: the eight corners are singularities (valence 3).
+        let points = (0..3)
+            .cartesian_product(0..3)
+            .cartesian_product(0..3)
+            .map(|((x, y), z)| [x, y, z])
+            .filter(|p| p.iter().any(|&c| c != 1))
+            .collect_vec();
+        let index = |p: [usize; 3]| points.iter().position(|&q| q == p).unwrap();
+        let mut faces = vec![];
+        for axis in 0..3 {
+            for side in [0, 2] {
+                for (u, v) in (0..2).cartesian_product(0..2) {
+                    let point = |du: usize, dv: usize| {
+                        let mut p = [0; 3];
+                        p[axis] = side;
+                        p[(axis + 1) % 3] = u + du;
+                        p[(axis + 2) % 3] = v + dv;
+                        index(p)
+                    };
+                    faces.push(vec![point(0, 0), point(1, 0), point(1, 1), point(0, 1)]);
+                }
+            }
+        }
+
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.singularities().is_ok_and(|singularities| singularities.len() == 8));
+            let separatrices = douconel.separatrices();
+            assert!(separatrices.is_ok_and(|separatrices| separatrices.len() == 12 && separatrices.iter().all(|separatrix| separatrix.len() == 2)));
+            assert!(douconel.patches().is_ok_and(|patches| patches.iter().all(|patch| patch.len() == 4)));
+
+            let layout = douconel.patch_layout();
+            assert!(layout.is_ok(), "{layout:?}");
+            if let Ok((layout, vert_map, face_to_patch)) = layout {
+                assert!(layout.nr_verts() == 8 && layout.nr_edges() == 12 * 2 && layout.nr_faces() == 6);
+                assert!(layout.genus() == 0);
+                assert!(vert_map.left_values().all(|&vert_id| douconel.valence(vert_id) == 3));
+                assert!(face_to_patch.len() == 24);
+                assert!(face_to_patch.values().counts().values().all(|&count| count == 4));
+                // Every (fine) face touches exactly one cube corner, which is a corner of its patch.
+                for face_id in douconel.face_ids() {
+                    let corners = douconel
+                        .corners(face_id)
+                        .into_iter()
+                        .filter_map(|vert_id| vert_map.get_by_left(&vert_id).copied())
+                        .collect_vec();
+                    assert!(corners.len() == 1);
+                    assert!(layout.corners(face_to_patch[&face_id]).contains(&corners[0]));
+                }
+            }
+        }
+
+        // A 3x3 grid of quads has no singularities: it is a single patch with the four boundary corners.
+        let v = |x: usize, y: usize| y * 4 + x;
+        let faces = (0..3)
+            .cartesian_product(0..3)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.singularities().is_ok_and(|singularities| singularities.is_empty()));
+            assert!(douconel.separatrices().is_ok_and(|separatrices| separatrices.is_empty()));
+            let layout = douconel.patch_layout();
+            assert!(layout.is_ok(), "{layout:?}");
+            if let Ok((layout, vert_map, _)) = layout {
+                assert!(layout.nr_verts() == 4 && layout.nr_faces() == 1);
+                assert!(vert_map.left_values().all(|&vert_id| douconel.valence(vert_id) == 2));
+            }
+        }
+
+        // A quad torus has no singularities, so its single patch is not a disk.
+        let v = |x: usize, y: usize| (y % 4) * 4 + (x % 4);
+        let faces = (0..4)
+            .cartesian_product(0..4)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(matches!(douconel.patch_layout(), Err(MeshError::PatchNotDisk(0))));
+        }
+
+        // A mesh with triangles is not a quad mesh.
+        let faces = vec![vec![0, 2, 1], vec![0, 1, 3], vec![1, 2, 3], vec![0, 3, 2]];
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(matches!(douconel.singularities(), Err(MeshError::NotQuad(_))));
+            assert!(matches!(douconel.separatrices(), Err(MeshError::NotQuad(_))));
+            assert!(matches!(douconel.patches(), Err(MeshError::NotQuad(_))));
+            assert!(matches!(douconel.patch_layout(), Err(MeshError::NotQuad(_))));
+        }
+    }
+
+    #[test]
+    fn quad_patch_layout_boundary() {
+        // An L-shaped grid: separatrices are traced from its concave boundary corner, and split it into three quad patches.
+        let v = |x: usize, y: usize| y * 5 + x;
+        let faces = (0..4)
+            .cartesian_product(0..4)
+            .filter(|&(x, y)| x < 2 || y < 2)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, vert_map, _)) = douconel {
+            let concave = vert_map.get_by_left(&v(2, 2)).copied().unwrap();
+            let separatrices = douconel.separatrices();
+            assert!(separatrices.is_ok_and(|separatrices| {
+                separatrices.len() == 2
+                    && separatrices
+                        .iter()
+                        .all(|separatrix| separatrix.len() == 2 && douconel.root(separatrix[0]) == concave)
+            }));
+            let layout = douconel.patch_layout();
+            assert!(layout.is_ok(), "{layout:?}");
+            if let Ok((layout, _, _)) = layout {
+                assert!(layout.nr_verts() == 8 && layout.nr_faces() == 3);
+                assert!(layout.face_ids().into_iter().all(|face_id| layout.is_quad(face_id)));
+            }
+        }
+
+        // A cylinder with a quad attached to its boundary: the two separatrices of the attached quad cut the cylinder into a narrow and a wide patch,
+        // and the side of the wide patch along the boundary ends at the same corners as the separatrix between the attached quad and the narrow patch.
+        let v = |x: usize, y: usize| y * 4 + x % 4;
+        let mut faces = (0..4)
+            .cartesian_product(0..2)
+            .map(|(x, y)| vec![v(x, y), v(x + 1, y), v(x + 1, y + 1), v(x, y + 1)])
+            .collect_vec();
+        faces.push(vec![12, 13, v(1, 0), v(0, 0)]);
+        let douconel = Douconel::<VertID, Empty, EdgeID, Empty, FaceID, Empty>::from_faces(&faces);
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            assert!(douconel.patches().is_ok_and(|patches| patches.len() == 3));
+            assert!(matches!(douconel.patch_layout(), Err(MeshError::PatchSidesShareCorners(..))));
+        }
+    }
+
+    #[test]
+    fn catmull_clark() {
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, usize, FaceID, usize>::from_file(&PathBuf::from("assets/hexahedron.obj"));
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((mut douconel, _, _)) = douconel {
+            for (label, face_id) in douconel.face_ids().into_iter().enumerate() {
+                douconel.faces[face_id] = label;
+            }
+            let e_ab = douconel.edge_ids()[0];
+            let e_ba = douconel.twin(e_ab);
+            douconel.edges[e_ab] = 1;
+            douconel.edges[e_ba] = 2;
+            let vert_ids = douconel.vert_ids();
+            let labels = douconel.add_vertex_property::<usize>("label");
+            for (label, &vert_id) in vert_ids.iter().enumerate() {
+                labels[vert_id] = label + 1;
+            }
+            douconel.add_edge_property::<bool>("crease")[e_ab] = true;
+
+            let subdivided = douconel.catmull_clark(2);
+            assert!(subdivided.is_ok(), "{subdivided:?}");
+            if let Ok((subdivided, vert_map, parents)) = subdivided {
+                assert!(subdivided.nr_faces() == 6 * 16);
+                assert!(subdivided.nr_verts() == 8 + 6 * 9 + 12 * 3);
+                assert!(subdivided.faces.keys().all(|face_id| subdivided.is_quad(face_id)));
+                assert!(subdivided.validate().is_valid());
+                assert!(parents.len() == subdivided.nr_faces());
+                assert!(parents.values().counts().values().all(|&count| count == 16));
+                assert!(
+                    parents
+                        .iter()
+                        .all(|(&face_id, &parent_id)| subdivided.faces[face_id] == douconel.faces[parent_id])
+                );
+
+                // The original vertices keep their properties, and the (four) quarters of every half-edge keep its data.
+                assert!(vert_map.len() == 8);
+                let labels = subdivided.vertex_property::<usize>("label").unwrap();
+                assert!(
+                    vert_map
+                        .iter()
+                        .all(|(&vert_id, &new_vert_id)| labels[new_vert_id] == douconel.vertex_property::<usize>("label").unwrap()[vert_id])
+                );
+                assert!(labels.values().filter(|&&label| label == 0).count() == subdivided.nr_verts() - 8);
+                assert!(subdivided.edges.values().filter(|&&label| label == 1).count() == 4);
+                assert!(subdivided.edges.values().filter(|&&label| label == 2).count() == 4);
+                let creases = subdivided.edge_property::<bool>("crease").unwrap();
+                assert!(
+                    subdivided
+                        .edge_ids()
+                        .into_iter()
+                        .all(|edge_id| creases[edge_id] == (subdivided.edges[edge_id] == 1))
+                );
+
+                // The cube is smoothed: all vertices move inside the original cube, and the patch layout is the cube again.
+                let (center, half_extents) = douconel.get_aabb();
+                assert!(subdivided.vert_ids().into_iter().all(|vert_id| {
+                    (subdivided.position(vert_id) - center)
+                        .abs()
+                        .iter()
+                        .zip(half_extents.iter())
+                        .all(|(d, h)| d < h)
+                }));
+                let layout = subdivided.patch_layout();
+                assert!(layout.is_ok(), "{layout:?}");
+                if let Ok((layout, _, _)) = layout {
+                    assert!(layout.nr_verts() == 8 && layout.nr_faces() == 6);
+                }
+            }
+        }
+
+        // Triangles become quads, and a flat open mesh stays flat.
+        let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_embedded_faces(
+            &[vec![0, 1, 2], vec![0, 2, 3]],
+            &[
+                Vector3D::new(0., 0., 0.),
+                Vector3D::new(1., 0., 0.),
+                Vector3D::new(1., 1., 0.),
+                Vector3D::new(0., 1., 0.),
+            ],
+        );
+        assert!(douconel.is_ok(), "{douconel:?}");
+        if let Ok((douconel, _, _)) = douconel {
+            let subdivided = douconel.catmull_clark(1);
+            assert!(subdivided.is_ok(), "{subdivided:?}");
+            if let Ok((subdivided, vert_map, _)) = subdivided {
+                assert!(subdivided.nr_faces() == 6);
+                assert!(subdivided.faces.keys().all(|face_id| subdivided.is_quad(face_id)));
+                assert!(subdivided.nr_boundary_loops() == 1);
+                assert!(subdivided.vert_ids().into_iter().all(|vert_id| subdivided.position(vert_id).z.abs() < 1e-12));
+                // The boundary corners (valence 2) are kept fixed.
+                for vert_id in douconel.vert_ids() {
+                    if douconel.valence(vert_id) == 2 {
+                        assert!(subdivided.position(vert_map.get_by_left(&vert_id).copied().unwrap()) == douconel.position(vert_id));
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn from_tetrahedron_obj() {
         let douconel = Douconel::<VertID, EmbeddedVertex, EdgeID, Empty, FaceID, Empty>::from_file(&PathBuf::from("assets/tetrahedron.obj"));